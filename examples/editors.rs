@@ -0,0 +1,208 @@
+use bevy::{
+    core_pipeline::clear_color::ClearColorConfig,
+    prelude::*,
+    render::{camera::Viewport, view::RenderLayers},
+    window::WindowResized,
+    winit::WinitSettings,
+};
+use bevy_flow_node::{
+    CursorCamera,
+    EvalContext,
+    FlowNodeInput,
+    FlowNodeOutput,
+    FlowNodePlugins,
+    FlowNodeSet,
+    FlowNodeTemplate,
+    NodeInputs,
+};
+
+fn main() {
+    App::new()
+        .insert_resource(WinitSettings::desktop_app())
+        .add_plugins(DefaultPlugins)
+        .add_plugins((
+            FlowNodePlugins::<NumberNodes>::default(),
+            FlowNodePlugins::<TextNodes>::default(),
+        ))
+        .add_systems(Startup, setup)
+        .add_systems(Update, split_viewports)
+        .run();
+}
+
+#[derive(Component)]
+struct Side(u32);
+
+#[derive(Clone)]
+enum NumberNodes {
+    Add,
+    Value(f32),
+}
+
+impl Default for NumberNodes {
+    fn default() -> Self {
+        Self::Value(0.0)
+    }
+}
+
+impl FlowNodeSet for NumberNodes {
+    type NodeIO = f32;
+    type State = ();
+    type Context = ();
+
+    fn resolve(
+        &self,
+        _context: &EvalContext<Self>,
+        _state: &mut Self::State,
+        inputs: &NodeInputs<Self>,
+        _output: Option<&str>,
+    ) -> Self::NodeIO {
+        match self {
+            NumberNodes::Add => inputs.iter().map(|(_, value)| value.unwrap_or(0.0)).sum(),
+            NumberNodes::Value(value) => *value,
+        }
+    }
+
+    fn template(self) -> FlowNodeTemplate<Self> {
+        match self {
+            NumberNodes::Add => FlowNodeTemplate {
+                title: "Add".to_string(),
+                inputs: Some(vec![
+                    FlowNodeInput::from_label("a"),
+                    FlowNodeInput::from_label("b"),
+                ]),
+                outputs: Some(vec![FlowNodeOutput::from_label("result")]),
+                node: self,
+                ..default()
+            },
+            NumberNodes::Value(_) => FlowNodeTemplate {
+                title: "Value".to_string(),
+                outputs: Some(vec![FlowNodeOutput::from_label("value")]),
+                node: self,
+                ..default()
+            },
+        }
+    }
+}
+
+#[derive(Clone)]
+enum TextNodes {
+    Join,
+    Text(String),
+}
+
+impl Default for TextNodes {
+    fn default() -> Self {
+        Self::Text(String::new())
+    }
+}
+
+impl FlowNodeSet for TextNodes {
+    type NodeIO = String;
+    type State = ();
+    type Context = ();
+
+    fn resolve(
+        &self,
+        _context: &EvalContext<Self>,
+        _state: &mut Self::State,
+        inputs: &NodeInputs<Self>,
+        _output: Option<&str>,
+    ) -> Self::NodeIO {
+        let get = |label: &str| inputs.get(label).unwrap_or_default();
+
+        match self {
+            TextNodes::Join => format!("{}{}", get("a"), get("b")),
+            TextNodes::Text(text) => text.clone(),
+        }
+    }
+
+    fn template(self) -> FlowNodeTemplate<Self> {
+        match self {
+            TextNodes::Join => FlowNodeTemplate {
+                title: "Join".to_string(),
+                inputs: Some(vec![
+                    FlowNodeInput::from_label("a"),
+                    FlowNodeInput::from_label("b"),
+                ]),
+                outputs: Some(vec![FlowNodeOutput::from_label("text")]),
+                node: self,
+                ..default()
+            },
+            TextNodes::Text(_) => FlowNodeTemplate {
+                title: "Text".to_string(),
+                outputs: Some(vec![FlowNodeOutput::from_label("text")]),
+                node: self,
+                ..default()
+            },
+        }
+    }
+}
+
+fn setup(mut commands: Commands) {
+    let numbers = commands
+        .spawn((
+            Camera2dBundle::default(),
+            CursorCamera,
+            RenderLayers::layer(1),
+            Side(0),
+        ))
+        .id();
+    let text = commands
+        .spawn((
+            Camera2dBundle {
+                camera: Camera {
+                    order: 1,
+                    ..default()
+                },
+                camera_2d: Camera2d {
+                    clear_color: ClearColorConfig::Custom(Color::rgb(0.16, 0.16, 0.2)),
+                },
+                ..default()
+            },
+            CursorCamera,
+            RenderLayers::layer(2),
+            Side(1),
+        ))
+        .id();
+
+    for (i, node) in [NumberNodes::Value(1.0), NumberNodes::Add]
+        .into_iter()
+        .enumerate()
+    {
+        let mut template = node.template();
+
+        template.editor = Some(numbers);
+        template.position = Vec2::new(-100.0 + 200.0 * i as f32, 0.0);
+        commands.spawn(template);
+    }
+
+    for (i, node) in [TextNodes::Text("Hello".to_string()), TextNodes::Join]
+        .into_iter()
+        .enumerate()
+    {
+        let mut template = node.template();
+
+        template.editor = Some(text);
+        template.position = Vec2::new(-100.0 + 200.0 * i as f32, 0.0);
+        commands.spawn(template);
+    }
+}
+
+fn split_viewports(
+    windows: Query<&Window>,
+    mut ev_resize: EventReader<WindowResized>,
+    mut q_cameras: Query<(&mut Camera, &Side)>,
+) {
+    for ev in ev_resize.read() {
+        let window = windows.get(ev.window).unwrap();
+        let size = UVec2::new(window.physical_width() / 2, window.physical_height());
+
+        for (mut camera, side) in q_cameras.iter_mut() {
+            camera.viewport = Some(Viewport {
+                physical_position: UVec2::new(size.x * side.0, 0),
+                physical_size: size,
+                ..default()
+            });
+        }
+    }
+}
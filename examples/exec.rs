@@ -0,0 +1,193 @@
+use bevy::{prelude::*, winit::WinitSettings};
+use bevy_flow_node::{
+    widgets::{InputWidget, InputWidgetPlugin},
+    EditorFocus,
+    EvalContext,
+    ExecTrigger,
+    FlowNodeEvent,
+    FlowNodeInput,
+    FlowNodeMenu,
+    FlowNodeMenuPlugin,
+    FlowNodeOutput,
+    FlowNodePlugins,
+    FlowNodeSet,
+    FlowNodeSlot,
+    FlowNodeTemplate,
+    NodeInputs,
+    PanCameraPlugin,
+    ResetNodeState,
+    SlotWidget,
+};
+
+fn main() {
+    App::new()
+        .insert_resource(WinitSettings::desktop_app())
+        .add_plugins(DefaultPlugins)
+        .add_plugins(FlowNodePlugins::<ExecNodes>::default())
+        .add_plugins((
+            PanCameraPlugin,
+            FlowNodeMenuPlugin::<ExecMenu, ExecNodes>::default(),
+            InputWidgetPlugin::<ExecNodes, String>::default(),
+        ))
+        .add_systems(Startup, setup)
+        .add_systems(Update, (print_messages, reset_on_r, trigger_on_space))
+        .run();
+}
+
+#[derive(Default, Resource)]
+struct ExecMenu;
+
+impl FlowNodeMenu<ExecNodes> for ExecMenu {
+    fn options(&self) -> Vec<(String, ExecNodes)> {
+        vec![
+            ("On space".to_string(), ExecNodes::OnSpace),
+            ("Text".to_string(), ExecNodes::Text(String::new())),
+            ("Count".to_string(), ExecNodes::Count),
+            ("Join".to_string(), ExecNodes::Join),
+            ("Print".to_string(), ExecNodes::Print),
+        ]
+    }
+}
+
+#[derive(Clone, Default)]
+enum ExecNodes {
+    #[default]
+    OnSpace,
+    Count,
+    Join,
+    Print,
+    Text(String),
+}
+
+impl FlowNodeSet for ExecNodes {
+    type NodeIO = String;
+    type State = u32;
+    type Context = ();
+
+    fn resolve(
+        &self,
+        _context: &EvalContext<Self>,
+        state: &mut Self::State,
+        inputs: &NodeInputs<Self>,
+        _output: Option<&str>,
+    ) -> Self::NodeIO {
+        let get = |label: &str| inputs.get(label).unwrap_or_default();
+
+        match self {
+            // Counts how often it is read, press R to start over.
+            ExecNodes::Count => {
+                *state += 1;
+                state.to_string()
+            }
+            ExecNodes::Join => format!("{}{}", get("a"), get("b")),
+            ExecNodes::Text(text) => text.clone(),
+            _ => String::new(),
+        }
+    }
+
+    fn template(self) -> FlowNodeTemplate<Self> {
+        match self {
+            ExecNodes::OnSpace => FlowNodeTemplate {
+                event: Some("space".to_string()),
+                title: "On space".to_string(),
+                outputs: Some(vec![FlowNodeOutput::exec("then")]),
+                node: self,
+                ..default()
+            },
+            ExecNodes::Count => FlowNodeTemplate {
+                title: "Count".to_string(),
+                outputs: Some(vec![FlowNodeOutput::from_label("count")]),
+                node: self,
+                ..default()
+            },
+            ExecNodes::Join => FlowNodeTemplate {
+                title: "Join".to_string(),
+                inputs: Some(vec![
+                    FlowNodeInput::from_label("a"),
+                    FlowNodeInput::from_label("b"),
+                ]),
+                outputs: Some(vec![FlowNodeOutput::from_label("text")]),
+                node: self,
+                ..default()
+            },
+            ExecNodes::Print => FlowNodeTemplate {
+                title: "Print".to_string(),
+                inputs: Some(vec![
+                    FlowNodeInput::exec("exec"),
+                    FlowNodeInput::from_label("message"),
+                ]),
+                outputs: Some(vec![FlowNodeOutput::exec("then")]),
+                node: self,
+                ..default()
+            },
+            ExecNodes::Text(_) => FlowNodeTemplate {
+                title: "Text".to_string(),
+                outputs: Some(vec![FlowNodeOutput::from_label("text")]),
+                node: self,
+                slot: Some(FlowNodeSlot::new(20.0)),
+                ..default()
+            },
+        }
+    }
+}
+
+impl SlotWidget<Self, InputWidget<String>> for ExecNodes {
+    fn get_widget(&self) -> Option<InputWidget<String>> {
+        match self {
+            ExecNodes::Text(_) => Some(InputWidget::default()),
+            _ => None,
+        }
+    }
+
+    fn set_value(&mut self, value: String) {
+        if let ExecNodes::Text(text) = self {
+            *text = value;
+        }
+    }
+}
+
+fn setup(mut commands: Commands) {
+    let mut event = ExecNodes::OnSpace.template();
+    let mut first = ExecNodes::Print.template();
+    let mut second = ExecNodes::Print.template();
+
+    event.position = Vec2::new(-300.0, 100.0);
+    first.position = Vec2::new(0.0, 100.0);
+    second.position = Vec2::new(300.0, 100.0);
+
+    commands.spawn(event);
+    commands.spawn(first);
+    commands.spawn(second);
+}
+
+fn reset_on_r(
+    focus: Res<EditorFocus>,
+    keys: Res<Input<KeyCode>>,
+    mut ev_reset: EventWriter<ResetNodeState>,
+) {
+    if *focus == EditorFocus::Canvas && keys.just_pressed(KeyCode::R) {
+        ev_reset.send(ResetNodeState::All);
+    }
+}
+
+fn trigger_on_space(
+    focus: Res<EditorFocus>,
+    keys: Res<Input<KeyCode>>,
+    mut ev_trigger: EventWriter<ExecTrigger>,
+) {
+    if *focus == EditorFocus::Canvas && keys.just_pressed(KeyCode::Space) {
+        ev_trigger.send(ExecTrigger("space".to_string()));
+    }
+}
+
+fn print_messages(mut ev_node: EventReader<FlowNodeEvent<ExecNodes>>) {
+    for ev in ev_node.read() {
+        if let FlowNodeEvent::Executed((entity, inputs)) = ev {
+            let message = inputs.iter().find(|(label, _)| label == "message");
+
+            if let Some((_, Some(message))) = message {
+                info!("{:?}: {}", entity, message);
+            }
+        }
+    }
+}
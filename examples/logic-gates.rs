@@ -1,6 +1,7 @@
 use bevy::{prelude::*, winit::WinitSettings};
 
 use bevy_flow_node::{
+    EvalContext,
     FlowNodeInput,
     FlowNodeMenu,
     FlowNodeMenuPlugin,
@@ -8,8 +9,10 @@ use bevy_flow_node::{
     FlowNodePlugins,
     FlowNodeSet,
     FlowNodeTemplate,
+    NodeInputs,
     PanCameraPlugin,
 };
+use std::collections::HashMap;
 
 fn main() {
     App::new()
@@ -35,20 +38,25 @@ enum LogicNodes {
     Nand,
     Nor,
     Xnor,
+    HalfAdder,
     #[default]
     Result,
 }
 
 impl FlowNodeSet for LogicNodes {
     type NodeIO = bool;
+    type State = ();
+    type Context = ();
 
     fn resolve(
         &self,
-        inputs: std::collections::HashMap<String, Option<Self::NodeIO>>,
+        _context: &EvalContext<Self>,
+        _state: &mut Self::State,
+        inputs: &NodeInputs<Self>,
         output: Option<&str>,
     ) -> Self::NodeIO {
-        let a = inputs.get("a").unwrap_or(&None).unwrap_or(false);
-        let b = inputs.get("b").unwrap_or(&None).unwrap_or(false);
+        let a = inputs.get_or("a", false);
+        let b = inputs.get_or("b", false);
 
         match self {
             LogicNodes::Input => output == Some("true"),
@@ -59,6 +67,8 @@ impl FlowNodeSet for LogicNodes {
             LogicNodes::Nand => !(a && b),
             LogicNodes::Nor => !(a || b),
             LogicNodes::Xnor => !(a ^ b),
+            // Resolved all at once by `resolve_outputs`.
+            LogicNodes::HalfAdder => a ^ b,
             LogicNodes::Result => {
                 let r = a;
 
@@ -69,6 +79,24 @@ impl FlowNodeSet for LogicNodes {
         }
     }
 
+    fn resolve_outputs(
+        &self,
+        _context: &EvalContext<Self>,
+        _state: &mut Self::State,
+        inputs: &NodeInputs<Self>,
+    ) -> Option<HashMap<String, Self::NodeIO>> {
+        let LogicNodes::HalfAdder = self else {
+            return None;
+        };
+        let a = inputs.get_or("a", false);
+        let b = inputs.get_or("b", false);
+
+        Some(HashMap::from([
+            ("sum".to_string(), a ^ b),
+            ("carry".to_string(), a && b),
+        ]))
+    }
+
     fn template(self) -> FlowNodeTemplate<Self> {
         match self {
             Self::Input => FlowNodeTemplate {
@@ -147,6 +175,19 @@ impl FlowNodeSet for LogicNodes {
                 node: self,
                 ..default()
             },
+            Self::HalfAdder => FlowNodeTemplate {
+                title: "Half adder".to_string(),
+                inputs: Some(vec![
+                    FlowNodeInput::from_label("a"),
+                    FlowNodeInput::from_label("b"),
+                ]),
+                outputs: Some(vec![
+                    FlowNodeOutput::from_label("sum"),
+                    FlowNodeOutput::from_label("carry"),
+                ]),
+                node: self,
+                ..default()
+            },
             Self::Result => FlowNodeTemplate {
                 title: "Result".to_string(),
                 inputs: Some(vec![FlowNodeInput::from_label("a")]),
@@ -171,6 +212,7 @@ impl FlowNodeMenu<LogicNodes> for LogicMenu {
             ("Nand".to_string(), LogicNodes::Nand),
             ("Nor".to_string(), LogicNodes::Nor),
             ("Xnor".to_string(), LogicNodes::Xnor),
+            ("Half adder".to_string(), LogicNodes::HalfAdder),
             ("Result".to_string(), LogicNodes::Result),
         ]
     }
@@ -1,6 +1,9 @@
-use bevy::{prelude::*, winit::WinitSettings};
+use bevy::prelude::*;
 use bevy_flow_node::{
     widgets::{DisplayWidget, DisplayWidgetPlugin, InputWidget, InputWidgetPlugin, NumberInput},
+    EvalContext,
+    EvaluationMode,
+    FlowNodeCommands,
     FlowNodeInput,
     FlowNodeMenu,
     FlowNodeMenuPlugin,
@@ -9,19 +12,24 @@ use bevy_flow_node::{
     FlowNodeSet,
     FlowNodeSlot,
     FlowNodeTemplate,
+    GridPlugin,
+    MinimapPlugin,
+    NodeInputs,
     PanCameraPlugin,
     SlotWidget,
 };
-use std::collections::HashMap;
 
 fn main() {
     App::new()
         .insert_resource(ClearColor(Color::rgb(0.12, 0.12, 0.12)))
-        .insert_resource(WinitSettings::desktop_app())
+        // Keeps the Sine node moving.
+        .insert_resource(EvaluationMode::EveryFrame)
         .add_plugins(DefaultPlugins)
         .add_plugins(FlowNodePlugins::<MathNodes>::default())
         .add_plugins((
             PanCameraPlugin,
+            GridPlugin,
+            MinimapPlugin,
             FlowNodeMenuPlugin::<MathMenu, MathNodes>::default(),
             DisplayWidgetPlugin::<MathNodes>::default(),
             InputWidgetPlugin::<MathNodes, NumberInput>::default(),
@@ -42,6 +50,8 @@ impl FlowNodeMenu<MathNodes> for MathMenu {
             ),
             ("Add".to_string(), MathNodes::Add),
             ("Multiply".to_string(), MathNodes::Mult),
+            ("Sine".to_string(), MathNodes::Sine),
+            ("Zoom".to_string(), MathNodes::Zoom),
             ("Output".to_string(), MathNodes::Output),
         ]
     }
@@ -52,7 +62,9 @@ enum MathNodes {
     Add,
     Mult,
     Output,
+    Sine,
     Value(NumberInput),
+    Zoom,
 }
 
 impl Default for MathNodes {
@@ -63,20 +75,37 @@ impl Default for MathNodes {
 
 impl FlowNodeSet for MathNodes {
     type NodeIO = f32;
+    type State = ();
+    type Context = Query<'static, 'static, &'static OrthographicProjection>;
 
     fn resolve(
         &self,
-        inputs: HashMap<String, Option<Self::NodeIO>>,
+        context: &EvalContext<Self>,
+        _state: &mut Self::State,
+        inputs: &NodeInputs<Self>,
         _output: Option<&str>,
     ) -> Self::NodeIO {
-        let a = inputs.get("a").unwrap_or(&None).unwrap_or(0.0);
-        let b = inputs.get("b").unwrap_or(&None).unwrap_or(0.0);
+        let a = inputs.get_or("a", 0.0);
+        let b = inputs.get_or("b", 0.0);
 
         match self {
             MathNodes::Add => a + b,
             MathNodes::Mult => a * b,
-            MathNodes::Output => inputs["value"].unwrap_or(0.0),
+            MathNodes::Output => inputs.get_or("value", 0.0),
+            MathNodes::Sine => context.time.elapsed_seconds().sin(),
             MathNodes::Value(value) => value.value,
+            MathNodes::Zoom => context
+                .params
+                .iter()
+                .next()
+                .map_or(1.0, |projection| projection.scale),
+        }
+    }
+
+    fn can_delete(&self) -> Result<(), String> {
+        match self {
+            MathNodes::Output => Err("The graph needs an output".to_string()),
+            _ => Ok(()),
         }
     }
 
@@ -112,6 +141,12 @@ impl FlowNodeSet for MathNodes {
                 }),
                 ..default()
             },
+            Self::Sine => FlowNodeTemplate {
+                title: "Sine".to_string(),
+                outputs: Some(vec![FlowNodeOutput::from_label("sin(time)")]),
+                node: self,
+                ..default()
+            },
             Self::Value(_) => FlowNodeTemplate {
                 title: "Value".to_string(),
                 outputs: Some(vec![FlowNodeOutput::from_label("value")]),
@@ -122,6 +157,12 @@ impl FlowNodeSet for MathNodes {
                 }),
                 ..default()
             },
+            Self::Zoom => FlowNodeTemplate {
+                title: "Zoom".to_string(),
+                outputs: Some(vec![FlowNodeOutput::from_label("scale")]),
+                node: self,
+                ..default()
+            },
         }
     }
 }
@@ -151,5 +192,12 @@ impl SlotWidget<Self, InputWidget<NumberInput>> for MathNodes {
 }
 
 fn setup(mut commands: Commands) {
-    commands.spawn(MathNodes::Output.template());
+    let a = commands.spawn_node(MathNodes::default(), Vec2::new(-300.0, 80.0));
+    let b = commands.spawn_node(MathNodes::default(), Vec2::new(-300.0, -80.0));
+    let add = commands.spawn_node(MathNodes::Add, Vec2::ZERO);
+    let output = commands.spawn_node(MathNodes::Output, Vec2::new(300.0, 0.0));
+
+    commands.connect(a, "value", add, "a");
+    commands.connect(b, "value", add, "b");
+    commands.connect(add, "result", output, "value");
 }
@@ -0,0 +1,128 @@
+use bevy::{
+    core_pipeline::clear_color::ClearColorConfig,
+    prelude::*,
+    render::view::RenderLayers,
+    winit::WinitSettings,
+};
+use bevy_flow_node::{
+    CursorCamera,
+    EditorPanel,
+    EvalContext,
+    FlowNodeInput,
+    FlowNodeOutput,
+    FlowNodePlugins,
+    FlowNodeSet,
+    FlowNodeTemplate,
+    NodeInputs,
+};
+
+fn main() {
+    App::new()
+        .insert_resource(WinitSettings::desktop_app())
+        .add_plugins(DefaultPlugins)
+        .add_plugins(FlowNodePlugins::<PanelNodes>::default())
+        .add_systems(Startup, setup)
+        .run();
+}
+
+#[derive(Clone, Copy, Default)]
+struct PanelNodes;
+
+impl FlowNodeSet for PanelNodes {
+    type NodeIO = ();
+    type State = ();
+    type Context = ();
+
+    fn resolve(
+        &self,
+        _context: &EvalContext<Self>,
+        _state: &mut Self::State,
+        _inputs: &NodeInputs<Self>,
+        _output: Option<&str>,
+    ) -> Self::NodeIO {
+    }
+
+    fn template(self) -> FlowNodeTemplate<Self> {
+        FlowNodeTemplate::default()
+    }
+}
+
+fn setup(mut commands: Commands) {
+    // Draws the surrounding UI, the editor draws its nodes on its own layer.
+    commands.spawn(Camera2dBundle::default());
+
+    let editor = commands
+        .spawn((
+            Camera2dBundle {
+                camera: Camera {
+                    order: 1,
+                    ..default()
+                },
+                camera_2d: Camera2d {
+                    clear_color: ClearColorConfig::Custom(Color::rgb(0.16, 0.16, 0.2)),
+                },
+                ..default()
+            },
+            UiCameraConfig { show_ui: false },
+            CursorCamera,
+            RenderLayers::layer(1),
+        ))
+        .id();
+
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        width: Val::Px(200.0),
+                        padding: UiRect::all(Val::Px(10.0)),
+                        ..default()
+                    },
+                    background_color: Color::rgb(0.2, 0.2, 0.2).into(),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section(
+                        "Inspector",
+                        TextStyle {
+                            font_size: 20.0,
+                            ..default()
+                        },
+                    ));
+                });
+            parent.spawn((
+                NodeBundle {
+                    style: Style {
+                        flex_grow: 1.0,
+                        margin: UiRect::all(Val::Px(20.0)),
+                        ..default()
+                    },
+                    ..default()
+                },
+                EditorPanel(editor),
+            ));
+        });
+
+    commands.spawn(FlowNodeTemplate::<PanelNodes> {
+        editor: Some(editor),
+        position: Vec2::new(-150.0, 0.0),
+        title: "Output".to_string(),
+        outputs: Some(vec![FlowNodeOutput::from_label("out")]),
+        ..default()
+    });
+    commands.spawn(FlowNodeTemplate::<PanelNodes> {
+        editor: Some(editor),
+        position: Vec2::new(150.0, 0.0),
+        title: "Input".to_string(),
+        inputs: Some(vec![FlowNodeInput::from_label("in")]),
+        ..default()
+    });
+}
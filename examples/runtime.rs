@@ -0,0 +1,124 @@
+use bevy::{app::ScheduleRunnerPlugin, log::LogPlugin, prelude::*};
+use bevy_flow_node::{
+    EvalContext,
+    EvaluationMode,
+    FlowNodeRuntimePlugin,
+    FlowNodeSet,
+    NodeInputs,
+    RuntimeGraph,
+    RuntimeSet,
+    SavedGraph,
+};
+#[cfg(feature = "editor")]
+use bevy_flow_node::{FlowNodeInput, FlowNodeOutput, FlowNodeTemplate};
+use serde::{Deserialize, Serialize};
+use std::{fs, time::Duration};
+
+// Runs without the editor: cargo run --example runtime --no-default-features --features serde
+fn main() {
+    App::new()
+        .add_plugins((
+            MinimalPlugins.set(ScheduleRunnerPlugin::run_loop(Duration::from_secs_f32(0.5))),
+            LogPlugin::default(),
+        ))
+        .add_plugins(FlowNodeRuntimePlugin::<WaveNodes>::default())
+        .insert_resource(EvaluationMode::EveryFrame)
+        .add_systems(Startup, setup)
+        .add_systems(
+            Update,
+            (
+                set_amplitude.before(RuntimeSet),
+                print_output.after(RuntimeSet),
+            ),
+        )
+        .run();
+}
+
+#[derive(Clone, Default, Deserialize, Serialize)]
+enum WaveNodes {
+    #[default]
+    Amplitude,
+    Output,
+    Wave,
+}
+
+impl FlowNodeSet for WaveNodes {
+    type NodeIO = f32;
+    type State = ();
+    type Context = ();
+
+    fn resolve(
+        &self,
+        context: &EvalContext<Self>,
+        _state: &mut Self::State,
+        inputs: &NodeInputs<Self>,
+        _output: Option<&str>,
+    ) -> Self::NodeIO {
+        match self {
+            WaveNodes::Amplitude => inputs.get_or("amplitude", 1.0),
+            WaveNodes::Output => inputs.get_or("value", 0.0),
+            WaveNodes::Wave => {
+                inputs.get_or("amplitude", 1.0) * context.time.elapsed_seconds().sin()
+            }
+        }
+    }
+
+    #[cfg(feature = "editor")]
+    fn template(self) -> FlowNodeTemplate<Self> {
+        match self {
+            WaveNodes::Amplitude => FlowNodeTemplate {
+                title: "Amplitude".to_string(),
+                inputs: Some(vec![FlowNodeInput::from_label("amplitude")]),
+                outputs: Some(vec![FlowNodeOutput::from_label("amplitude")]),
+                node: self,
+                ..default()
+            },
+            WaveNodes::Output => FlowNodeTemplate {
+                title: "Output".to_string(),
+                inputs: Some(vec![FlowNodeInput::from_label("value")]),
+                node: self,
+                ..default()
+            },
+            WaveNodes::Wave => FlowNodeTemplate {
+                title: "Wave".to_string(),
+                inputs: Some(vec![FlowNodeInput::from_label("amplitude")]),
+                outputs: Some(vec![FlowNodeOutput::from_label("wave")]),
+                node: self,
+                ..default()
+            },
+        }
+    }
+}
+
+// A graph saved from the editor with FlowNodeGraph::save and written out with ron.
+fn setup(mut commands: Commands) {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/assets/wave.ron");
+    let graph: SavedGraph<WaveNodes> = match fs::read_to_string(path) {
+        Ok(saved) => ron::from_str(&saved).expect("wave.ron is a saved graph of WaveNodes"),
+        Err(error) => panic!("Could not read {path}: {error}"),
+    };
+
+    commands.spawn(RuntimeGraph::load(&graph));
+}
+
+fn set_amplitude(time: Res<Time>, mut q_graphs: Query<&mut RuntimeGraph<WaveNodes>>) {
+    for mut graph in q_graphs.iter_mut() {
+        let amplitude = 1.0 + (time.elapsed_seconds() / 4.0).floor();
+
+        let node = graph
+            .node("Amplitude")
+            .expect("wave.ron has one Amplitude node");
+
+        graph.set_input(node, "amplitude", amplitude);
+    }
+}
+
+fn print_output(q_graphs: Query<&RuntimeGraph<WaveNodes>>) {
+    for graph in q_graphs.iter() {
+        let node = graph.node("Output").expect("wave.ron has one Output node");
+
+        if let Some(value) = graph.output(node) {
+            info!("Output: {value:.3}");
+        }
+    }
+}
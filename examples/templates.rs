@@ -1,11 +1,18 @@
 use bevy::{prelude::*, winit::WinitSettings};
 use bevy_flow_node::{
+    AlignNodes,
+    AlignPlugin,
     CursorCamera,
+    EditorFocus,
+    EditorMode,
+    EvalContext,
     FlowNodeInput,
     FlowNodeOutput,
     FlowNodePlugins,
     FlowNodeSet,
     FlowNodeTemplate,
+    LayoutNodes,
+    NodeInputs,
 };
 
 fn main() {
@@ -13,8 +20,12 @@ fn main() {
         .insert_resource(ClearColor(Color::rgb(0.12, 0.12, 0.12)))
         .insert_resource(WinitSettings::desktop_app())
         .add_plugins(DefaultPlugins)
-        .add_plugins(FlowNodePlugins::<TemplateNodes>::default())
+        .add_plugins((
+            FlowNodePlugins::<TemplateNodes>::default(),
+            AlignPlugin::<TemplateNodes>::default(),
+        ))
         .add_systems(Startup, setup)
+        .add_systems(Update, (arrange_on_keys, toggle_read_only))
         .run();
 }
 
@@ -23,10 +34,14 @@ struct TemplateNodes;
 
 impl FlowNodeSet for TemplateNodes {
     type NodeIO = ();
+    type State = ();
+    type Context = ();
 
     fn resolve(
         &self,
-        _inputs: std::collections::HashMap<String, Option<Self::NodeIO>>,
+        _context: &EvalContext<Self>,
+        _state: &mut Self::State,
+        _inputs: &NodeInputs<Self>,
         _output: Option<&str>,
     ) -> Self::NodeIO {
     }
@@ -61,3 +76,51 @@ fn setup(mut commands: Commands) {
         });
     }
 }
+
+/// Shift click nodes to select several, then press a digit to arrange them, or press L to lay
+/// out the whole graph.
+fn arrange_on_keys(
+    focus: Res<EditorFocus>,
+    keys: Res<Input<KeyCode>>,
+    mut ev_align: EventWriter<AlignNodes>,
+    mut ev_layout: EventWriter<LayoutNodes>,
+) {
+    if *focus != EditorFocus::Canvas {
+        return;
+    }
+
+    let alignments = [
+        (KeyCode::Key1, AlignNodes::Left),
+        (KeyCode::Key2, AlignNodes::CenterX),
+        (KeyCode::Key3, AlignNodes::Right),
+        (KeyCode::Key4, AlignNodes::Top),
+        (KeyCode::Key5, AlignNodes::CenterY),
+        (KeyCode::Key6, AlignNodes::Bottom),
+        (KeyCode::Key7, AlignNodes::DistributeX),
+        (KeyCode::Key8, AlignNodes::DistributeY),
+    ];
+
+    for (key, alignment) in alignments {
+        if keys.just_pressed(key) {
+            ev_align.send(alignment);
+        }
+    }
+
+    if keys.just_pressed(KeyCode::L) {
+        ev_layout.send(LayoutNodes::All);
+    }
+}
+
+/// Press R to switch between editing and only viewing the graph.
+fn toggle_read_only(
+    focus: Res<EditorFocus>,
+    keys: Res<Input<KeyCode>>,
+    mut mode: ResMut<EditorMode>,
+) {
+    if *focus == EditorFocus::Canvas && keys.just_pressed(KeyCode::R) {
+        *mode = match *mode {
+            EditorMode::Editable => EditorMode::ReadOnly,
+            _ => EditorMode::Editable,
+        };
+    }
+}
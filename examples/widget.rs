@@ -2,15 +2,16 @@ use bevy::{prelude::*, winit::WinitSettings};
 use bevy_flow_node::{
     widgets::{DisplayWidget, DisplayWidgetPlugin, InputWidget, InputWidgetPlugin},
     CursorCamera,
+    EvalContext,
     FlowNodeInput,
     FlowNodeOutput,
     FlowNodePlugins,
     FlowNodeSet,
     FlowNodeSlot,
     FlowNodeTemplate,
+    NodeInputs,
     SlotWidget,
 };
-use std::collections::HashMap;
 
 fn main() {
     App::new()
@@ -32,6 +33,7 @@ enum IONodes {
     Output,
 }
 
+#[allow(clippy::derivable_impls)]
 impl Default for IONodes {
     fn default() -> Self {
         Self::Output
@@ -40,17 +42,19 @@ impl Default for IONodes {
 
 impl FlowNodeSet for IONodes {
     type NodeIO = String;
+    type State = ();
+    type Context = ();
 
     fn resolve(
         &self,
-        inputs: HashMap<String, Option<Self::NodeIO>>,
+        _context: &EvalContext<Self>,
+        _state: &mut Self::State,
+        inputs: &NodeInputs<Self>,
         _output: Option<&str>,
     ) -> Self::NodeIO {
-        let mut inputs = inputs;
-
         match self {
             IONodes::Input(s) => s.clone(),
-            IONodes::Output => inputs.remove("input").unwrap().unwrap_or("".to_string()),
+            IONodes::Output => inputs.get("input").unwrap_or_default(),
         }
     }
 
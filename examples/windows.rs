@@ -0,0 +1,92 @@
+use bevy::{
+    prelude::*,
+    render::{camera::RenderTarget, view::RenderLayers},
+    window::WindowRef,
+    winit::WinitSettings,
+};
+use bevy_flow_node::{
+    CursorCamera,
+    EvalContext,
+    FlowNodeInput,
+    FlowNodeOutput,
+    FlowNodePlugins,
+    FlowNodeSet,
+    FlowNodeTemplate,
+    NodeInputs,
+};
+
+fn main() {
+    App::new()
+        .insert_resource(WinitSettings::desktop_app())
+        .add_plugins(DefaultPlugins.set(WindowPlugin {
+            primary_window: Some(Window {
+                title: "Game".to_string(),
+                ..default()
+            }),
+            ..default()
+        }))
+        .add_plugins(FlowNodePlugins::<WindowNodes>::default())
+        .add_systems(Startup, setup)
+        .run();
+}
+
+#[derive(Clone, Copy, Default)]
+struct WindowNodes;
+
+impl FlowNodeSet for WindowNodes {
+    type NodeIO = ();
+    type State = ();
+    type Context = ();
+
+    fn resolve(
+        &self,
+        _context: &EvalContext<Self>,
+        _state: &mut Self::State,
+        _inputs: &NodeInputs<Self>,
+        _output: Option<&str>,
+    ) -> Self::NodeIO {
+    }
+
+    fn template(self) -> FlowNodeTemplate<Self> {
+        FlowNodeTemplate::default()
+    }
+}
+
+fn setup(mut commands: Commands) {
+    commands.spawn(Camera2dBundle::default());
+
+    let window = commands
+        .spawn(Window {
+            title: "Graph".to_string(),
+            ..default()
+        })
+        .id();
+    let editor = commands
+        .spawn((
+            Camera2dBundle {
+                camera: Camera {
+                    target: RenderTarget::Window(WindowRef::Entity(window)),
+                    ..default()
+                },
+                ..default()
+            },
+            CursorCamera,
+            RenderLayers::layer(1),
+        ))
+        .id();
+
+    commands.spawn(FlowNodeTemplate::<WindowNodes> {
+        editor: Some(editor),
+        position: Vec2::new(-150.0, 0.0),
+        title: "Output".to_string(),
+        outputs: Some(vec![FlowNodeOutput::from_label("out")]),
+        ..default()
+    });
+    commands.spawn(FlowNodeTemplate::<WindowNodes> {
+        editor: Some(editor),
+        position: Vec2::new(150.0, 0.0),
+        title: "Input".to_string(),
+        inputs: Some(vec![FlowNodeInput::from_label("in")]),
+        ..default()
+    });
+}
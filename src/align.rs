@@ -0,0 +1,471 @@
+#![allow(clippy::type_complexity)]
+
+use bevy::prelude::*;
+use bevy_prototype_lyon::{entity::ShapeBundle, prelude::*};
+use std::{collections::HashMap, marker::PhantomData};
+
+use crate::{
+    editor::{EditorSet, InEditor},
+    interactions::Clickable,
+    node::{ActiveNode, FlowNode, FlowNodeEvent, Locked, Selected},
+    node_set::FlowNodeSet,
+    theme::FlowNodeTheme,
+};
+
+// Left or bottom edge, centre, and right or top edge, relative to a node's size.
+const EDGES: [f32; 3] = [-0.5, 0.0, 0.5];
+
+/// Lines up [`Selected`] nodes of `N` on [`AlignNodes`] and shows alignment guides while
+/// dragging.
+pub struct AlignPlugin<N: FlowNodeSet>(PhantomData<N>);
+
+impl<N: FlowNodeSet> Default for AlignPlugin<N> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<N: FlowNodeSet> Plugin for AlignPlugin<N> {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<GuidePlugin>() {
+            app.add_plugins(GuidePlugin);
+        }
+
+        app.add_systems(Update, align_nodes::<N>.in_set(EditorSet::Layout));
+    }
+}
+
+// Shared by the align plugins of every node set.
+struct GuidePlugin;
+
+impl Plugin for GuidePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AlignConfig>()
+            .init_resource::<AlignmentGuides>()
+            .init_resource::<FlowNodeTheme>()
+            .add_event::<AlignNodes>()
+            .add_systems(Update, draw_guides.in_set(EditorSet::Render));
+
+        if !app.is_plugin_added::<ShapePlugin>() {
+            app.add_plugins(ShapePlugin);
+        }
+    }
+}
+
+#[derive(Resource)]
+pub struct AlignConfig {
+    /// Distance within which a dragged node lines up with the edges and centres of other
+    /// nodes, in pixels.
+    pub guide_threshold: f32,
+    pub guides: bool,
+}
+
+impl Default for AlignConfig {
+    fn default() -> Self {
+        Self {
+            guide_threshold: 6.0,
+            guides: true,
+        }
+    }
+}
+
+/// Arranges the [`Selected`] nodes of every editor.
+#[derive(Clone, Copy, Debug, Event, PartialEq, Eq)]
+pub enum AlignNodes {
+    Bottom,
+    /// Gives every node the same horizontal centre.
+    CenterX,
+    /// Gives every node the same vertical centre.
+    CenterY,
+    /// Spaces the nodes evenly from left to right.
+    DistributeX,
+    /// Spaces the nodes evenly from top to bottom.
+    DistributeY,
+    Left,
+    Right,
+    Top,
+}
+
+/// The guides found by the node being dragged this frame.
+#[derive(Default, Resource)]
+pub(crate) struct AlignmentGuides {
+    editor: Option<Entity>,
+    lines: Vec<(Vec2, Vec2)>,
+    shown: bool,
+    width: f32,
+}
+
+impl AlignmentGuides {
+    /// Moves a node dragged to `position` onto the edges and centres of `others` within
+    /// `threshold`, and remembers the guides it lined up with.
+    pub(crate) fn snap(
+        &mut self,
+        editor: Entity,
+        position: Vec2,
+        size: Vec2,
+        others: &[Rect],
+        threshold: f32,
+        width: f32,
+    ) -> Vec2 {
+        let closest = |axis: usize| {
+            others
+                .iter()
+                .flat_map(|&other| {
+                    EDGES.into_iter().flat_map(move |from| {
+                        EDGES.into_iter().map(move |to| {
+                            let target = other.center()[axis] + other.size()[axis] * to;
+
+                            (target, from, other)
+                        })
+                    })
+                })
+                .map(|(target, from, other)| {
+                    let moved = target - size[axis] * from;
+
+                    ((moved - position[axis]).abs(), moved, target, other)
+                })
+                .filter(|(distance, ..)| *distance <= threshold)
+                .min_by(|a, b| a.0.total_cmp(&b.0))
+        };
+        let mut snapped = position;
+        let x = closest(0);
+        let y = closest(1);
+
+        if let Some((_, moved, _, _)) = x {
+            snapped.x = moved;
+        }
+
+        if let Some((_, moved, _, _)) = y {
+            snapped.y = moved;
+        }
+
+        let node = Rect::from_center_size(snapped, size);
+
+        self.editor = Some(editor);
+        self.width = width;
+        self.lines.clear();
+
+        if let Some((_, _, target, other)) = x {
+            let span = node.union(other);
+
+            self.lines
+                .push((Vec2::new(target, span.min.y), Vec2::new(target, span.max.y)));
+        }
+
+        if let Some((_, _, target, other)) = y {
+            let span = node.union(other);
+
+            self.lines
+                .push((Vec2::new(span.min.x, target), Vec2::new(span.max.x, target)));
+        }
+
+        snapped
+    }
+}
+
+#[derive(Component)]
+struct AlignmentGuide;
+
+fn align_nodes<N: FlowNodeSet>(
+    mut ev_align: EventReader<AlignNodes>,
+    mut ev_node: EventWriter<FlowNodeEvent<N>>,
+    mut q_nodes: Query<
+        (Entity, &mut Transform, &Clickable, &InEditor, Has<Locked>),
+        (With<Selected>, With<FlowNode<N>>),
+    >,
+) {
+    for ev in ev_align.read() {
+        let mut editors = HashMap::<Entity, Vec<(Entity, Rect)>>::new();
+
+        for (entity, transform, clickable, editor, _) in q_nodes.iter() {
+            if let Clickable::Area(size) = clickable {
+                editors.entry(editor.0).or_default().push((
+                    entity,
+                    Rect::from_center_size(transform.translation.truncate(), *size),
+                ));
+            }
+        }
+
+        for mut nodes in editors.into_values() {
+            if nodes.len() < 2 {
+                continue;
+            }
+
+            let bounds = nodes
+                .iter()
+                .fold(nodes[0].1, |bounds, (_, rect)| bounds.union(*rect));
+            let mut moved = Vec::new();
+
+            match ev {
+                AlignNodes::Bottom => {
+                    for (entity, rect) in nodes.iter() {
+                        moved.push((*entity, None, Some(bounds.min.y + rect.half_size().y)));
+                    }
+                }
+                AlignNodes::CenterX => {
+                    for (entity, _) in nodes.iter() {
+                        moved.push((*entity, Some(bounds.center().x), None));
+                    }
+                }
+                AlignNodes::CenterY => {
+                    for (entity, _) in nodes.iter() {
+                        moved.push((*entity, None, Some(bounds.center().y)));
+                    }
+                }
+                AlignNodes::DistributeX => {
+                    let total = nodes.iter().map(|(_, rect)| rect.width()).sum::<f32>();
+                    let gap = (bounds.width() - total) / (nodes.len() - 1) as f32;
+                    let mut x = bounds.min.x;
+
+                    nodes.sort_by(|(_, a), (_, b)| a.center().x.total_cmp(&b.center().x));
+
+                    for (entity, rect) in nodes.iter() {
+                        moved.push((*entity, Some(x + rect.half_size().x), None));
+                        x += rect.width() + gap;
+                    }
+                }
+                AlignNodes::DistributeY => {
+                    let total = nodes.iter().map(|(_, rect)| rect.height()).sum::<f32>();
+                    let gap = (bounds.height() - total) / (nodes.len() - 1) as f32;
+                    let mut y = bounds.max.y;
+
+                    nodes.sort_by(|(_, a), (_, b)| b.center().y.total_cmp(&a.center().y));
+
+                    for (entity, rect) in nodes.iter() {
+                        moved.push((*entity, None, Some(y - rect.half_size().y)));
+                        y -= rect.height() + gap;
+                    }
+                }
+                AlignNodes::Left => {
+                    for (entity, rect) in nodes.iter() {
+                        moved.push((*entity, Some(bounds.min.x + rect.half_size().x), None));
+                    }
+                }
+                AlignNodes::Right => {
+                    for (entity, rect) in nodes.iter() {
+                        moved.push((*entity, Some(bounds.max.x - rect.half_size().x), None));
+                    }
+                }
+                AlignNodes::Top => {
+                    for (entity, rect) in nodes.iter() {
+                        moved.push((*entity, None, Some(bounds.max.y - rect.half_size().y)));
+                    }
+                }
+            }
+
+            for (entity, x, y) in moved {
+                // Locked nodes still line up the others.
+                if let Ok((_, mut transform, _, _, false)) = q_nodes.get_mut(entity) {
+                    let from = transform.translation.truncate();
+                    let to = Vec2::new(x.unwrap_or(from.x), y.unwrap_or(from.y));
+
+                    transform.translation.x = to.x;
+                    transform.translation.y = to.y;
+
+                    if to != from {
+                        ev_node.send(FlowNodeEvent::Moved { entity, from, to });
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn draw_guides(
+    mut commands: Commands,
+    mut guides: ResMut<AlignmentGuides>,
+    theme: Res<FlowNodeTheme>,
+    q_editors: Query<&ActiveNode>,
+    mut q_guides: Query<
+        (Entity, &mut Path, &mut Stroke, &mut Transform, &InEditor),
+        With<AlignmentGuide>,
+    >,
+) {
+    let Some(editor) = guides.editor else {
+        return;
+    };
+    // Nodes are layered 10 apart below the index of the next one brought to the front.
+    let z = q_editors
+        .get(editor)
+        .map_or(0.0, |active_node| active_node.index);
+
+    if guides.lines.is_empty() && !guides.shown {
+        return;
+    }
+
+    let mut path_builder = PathBuilder::new();
+
+    for (start, end) in guides.lines.iter() {
+        path_builder.move_to(*start);
+        path_builder.line_to(*end);
+    }
+
+    let path = ShapePath::build_as(&path_builder.build());
+    let stroke = Stroke::new(theme.node_border, guides.width);
+
+    if let Ok((entity, mut guide_path, mut guide_stroke, mut transform, in_editor)) =
+        q_guides.get_single_mut()
+    {
+        *guide_path = path;
+        *guide_stroke = stroke;
+        transform.translation.z = z;
+
+        if in_editor.0 != editor {
+            commands.entity(entity).insert(InEditor(editor));
+        }
+    } else {
+        commands.spawn((
+            ShapeBundle {
+                path,
+                spatial: SpatialBundle::from_transform(Transform::from_xyz(0.0, 0.0, z)),
+                ..default()
+            },
+            stroke,
+            AlignmentGuide,
+            InEditor(editor),
+        ));
+    }
+
+    // Guides are found again every frame a node is dragged.
+    guides.shown = !guides.lines.is_empty();
+    guides.lines.clear();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{cursor::tests::assert_near, runtime::tests::TestNodes};
+
+    fn align_app() -> App {
+        let mut app = App::new();
+
+        app.add_event::<AlignNodes>()
+            .add_event::<FlowNodeEvent<TestNodes>>()
+            .add_systems(Update, align_nodes::<TestNodes>);
+
+        app
+    }
+
+    fn spawn(app: &mut App, editor: Entity, position: Vec2, size: Vec2) -> Entity {
+        app.world
+            .spawn((
+                FlowNode(TestNodes::Pass),
+                Clickable::Area(size),
+                InEditor(editor),
+                Selected,
+                Transform::from_translation(position.extend(0.0)),
+            ))
+            .id()
+    }
+
+    fn position(app: &App, entity: Entity) -> Vec2 {
+        app.world
+            .get::<Transform>(entity)
+            .unwrap()
+            .translation
+            .truncate()
+    }
+
+    #[test]
+    fn align_left_lines_up_left_edges() {
+        let mut app = align_app();
+        let editor = app.world.spawn_empty().id();
+        let wide = spawn(
+            &mut app,
+            editor,
+            Vec2::new(0.0, 0.0),
+            Vec2::new(100.0, 20.0),
+        );
+        let narrow = spawn(
+            &mut app,
+            editor,
+            Vec2::new(30.0, 50.0),
+            Vec2::new(20.0, 20.0),
+        );
+        let locked = spawn(
+            &mut app,
+            editor,
+            Vec2::new(-40.0, 90.0),
+            Vec2::new(40.0, 20.0),
+        );
+        let unselected = app
+            .world
+            .spawn((
+                FlowNode(TestNodes::Pass),
+                Clickable::Area(Vec2::splat(20.0)),
+                InEditor(editor),
+                Transform::from_xyz(-200.0, 0.0, 0.0),
+            ))
+            .id();
+
+        app.world.entity_mut(locked).insert(Locked);
+        app.world.send_event(AlignNodes::Left);
+        app.update();
+
+        // The locked node stays put but still sets the edge the others line up on.
+        assert_near(position(&app, wide), Vec2::new(-10.0, 0.0));
+        assert_near(position(&app, narrow), Vec2::new(-50.0, 50.0));
+        assert_near(position(&app, locked), Vec2::new(-40.0, 90.0));
+        assert_near(position(&app, unselected), Vec2::new(-200.0, 0.0));
+    }
+
+    #[test]
+    fn distribute_spaces_nodes_evenly() {
+        let mut app = align_app();
+        let editor = app.world.spawn_empty().id();
+        let last = spawn(
+            &mut app,
+            editor,
+            Vec2::new(200.0, 0.0),
+            Vec2::new(20.0, 20.0),
+        );
+        let first = spawn(
+            &mut app,
+            editor,
+            Vec2::new(0.0, 10.0),
+            Vec2::new(20.0, 20.0),
+        );
+        let middle = spawn(
+            &mut app,
+            editor,
+            Vec2::new(30.0, 20.0),
+            Vec2::new(40.0, 20.0),
+        );
+
+        app.world.send_event(AlignNodes::DistributeX);
+        app.update();
+
+        // 220 wide with 80 taken by the nodes leaves two gaps of 70.
+        assert_near(position(&app, first), Vec2::new(0.0, 10.0));
+        assert_near(position(&app, middle), Vec2::new(100.0, 20.0));
+        assert_near(position(&app, last), Vec2::new(200.0, 0.0));
+    }
+
+    #[test]
+    fn snap_lines_up_within_threshold() {
+        let mut guides = AlignmentGuides::default();
+        let editor = Entity::from_raw(1);
+        let other = Rect::from_center_size(Vec2::ZERO, Vec2::new(100.0, 50.0));
+        let size = Vec2::new(40.0, 20.0);
+
+        // Only the left edges are close enough to line up.
+        let snapped = guides.snap(editor, Vec2::new(-28.5, 50.0), size, &[other], 5.0, 1.0);
+
+        assert_near(snapped, Vec2::new(-30.0, 50.0));
+        assert_eq!(
+            guides.lines,
+            vec![(Vec2::new(-50.0, -25.0), Vec2::new(-50.0, 60.0))]
+        );
+
+        // The bottom of the node lines up with the top of the other one.
+        let snapped = guides.snap(editor, Vec2::new(0.0, 37.0), size, &[other], 5.0, 1.0);
+
+        assert_near(snapped, Vec2::new(0.0, 35.0));
+        assert_eq!(guides.lines.len(), 2);
+
+        let snapped = guides.snap(editor, Vec2::new(80.0, 80.0), size, &[other], 5.0, 1.0);
+
+        assert_near(snapped, Vec2::new(80.0, 80.0));
+        assert!(guides.lines.is_empty());
+    }
+}
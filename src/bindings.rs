@@ -0,0 +1,190 @@
+use bevy::{ecs::system::SystemParam, prelude::*};
+
+use crate::focus::EditorFocus;
+
+/// Maps editor actions to the key chords and mouse buttons that trigger them.
+///
+/// Every action accepts several bindings; any one of them triggers the action. A binding
+/// starts an action only when its modifiers match exactly, so `Alt + Left` can pan the
+/// camera without also selecting a node. Held actions such as dragging or panning then
+/// continue for as long as the main key or button stays down, regardless of modifiers.
+#[derive(Clone, Resource)]
+pub struct FlowNodeBindings {
+    /// Key bindings close the menu when pressed, mouse bindings when released.
+    pub close_menu: Vec<Binding>,
+    pub delete_node: Vec<Binding>,
+    /// Held while dragging to place nodes freely instead of on the grid. Drags only start with
+    /// it held when `select` has a binding with the same modifier.
+    pub disable_snap: Vec<Binding>,
+    /// Adds the node under the cursor to the selection, or removes it.
+    pub extend_selection: Vec<Binding>,
+    pub frame_all: Vec<Binding>,
+    pub frame_selected: Vec<Binding>,
+    pub open_menu: Vec<Binding>,
+    pub pan_camera: Vec<Binding>,
+    pub pan_down: Vec<Binding>,
+    pub pan_left: Vec<Binding>,
+    pub pan_right: Vec<Binding>,
+    pub pan_up: Vec<Binding>,
+    pub select: Vec<Binding>,
+}
+
+impl Default for FlowNodeBindings {
+    fn default() -> Self {
+        Self {
+            close_menu: vec![
+                Binding::key(KeyCode::A),
+                Binding::key(KeyCode::Escape),
+                Binding::mouse(MouseButton::Left),
+                Binding::mouse(MouseButton::Right),
+            ],
+            delete_node: vec![Binding::key(KeyCode::Delete)],
+            disable_snap: vec![
+                Binding::key(KeyCode::ControlLeft),
+                Binding::key(KeyCode::ControlRight),
+            ],
+            extend_selection: vec![Binding::mouse(MouseButton::Left).with_shift()],
+            frame_all: vec![Binding::key(KeyCode::Home)],
+            frame_selected: vec![Binding::key(KeyCode::F)],
+            open_menu: vec![Binding::key(KeyCode::A)],
+            pan_camera: vec![Binding::mouse(MouseButton::Middle)],
+            pan_down: vec![Binding::key(KeyCode::Down)],
+            pan_left: vec![Binding::key(KeyCode::Left)],
+            pan_right: vec![Binding::key(KeyCode::Right)],
+            pan_up: vec![Binding::key(KeyCode::Up)],
+            select: vec![
+                Binding::mouse(MouseButton::Left),
+                // Starts a drag with snapping disabled.
+                Binding::mouse(MouseButton::Left).with_control(),
+            ],
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BindingInput {
+    Key(KeyCode),
+    Mouse(MouseButton),
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Modifiers {
+    pub alt: bool,
+    pub control: bool,
+    pub shift: bool,
+    pub super_key: bool,
+}
+
+impl Modifiers {
+    pub const NONE: Self = Self {
+        alt: false,
+        control: false,
+        shift: false,
+        super_key: false,
+    };
+
+    pub fn from_keys(keys: &Input<KeyCode>) -> Self {
+        Self {
+            alt: keys.any_pressed([KeyCode::AltLeft, KeyCode::AltRight]),
+            control: keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]),
+            shift: keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]),
+            super_key: keys.any_pressed([KeyCode::SuperLeft, KeyCode::SuperRight]),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Binding {
+    pub input: BindingInput,
+    pub modifiers: Modifiers,
+}
+
+impl Binding {
+    pub fn key(key: KeyCode) -> Self {
+        Self {
+            input: BindingInput::Key(key),
+            modifiers: Modifiers::NONE,
+        }
+    }
+
+    pub fn mouse(button: MouseButton) -> Self {
+        Self {
+            input: BindingInput::Mouse(button),
+            modifiers: Modifiers::NONE,
+        }
+    }
+
+    pub fn with_alt(mut self) -> Self {
+        self.modifiers.alt = true;
+        self
+    }
+
+    pub fn with_control(mut self) -> Self {
+        self.modifiers.control = true;
+        self
+    }
+
+    pub fn with_shift(mut self) -> Self {
+        self.modifiers.shift = true;
+        self
+    }
+
+    pub fn with_super(mut self) -> Self {
+        self.modifiers.super_key = true;
+        self
+    }
+}
+
+/// Reads keyboard and mouse state on behalf of [`FlowNodeBindings`].
+///
+/// Key bindings are routed through [`EditorFocus`] and only fire for the focused element,
+/// mouse bindings fire regardless of focus.
+#[derive(SystemParam)]
+pub struct BindingInputs<'w> {
+    focus: Res<'w, EditorFocus>,
+    keys: Res<'w, Input<KeyCode>>,
+    mouse: Res<'w, Input<MouseButton>>,
+}
+
+impl<'w> BindingInputs<'w> {
+    pub fn focus(&self) -> EditorFocus {
+        *self.focus
+    }
+
+    /// Returns `true` if any binding was triggered this frame with exactly its modifiers held
+    /// while the canvas has focus.
+    pub fn just_pressed(&self, bindings: &[Binding]) -> bool {
+        self.just_pressed_in(EditorFocus::Canvas, bindings)
+    }
+
+    /// Same as [`BindingInputs::just_pressed`] with key bindings routed to `focus`.
+    pub fn just_pressed_in(&self, focus: EditorFocus, bindings: &[Binding]) -> bool {
+        let modifiers = Modifiers::from_keys(&self.keys);
+
+        bindings.iter().any(|binding| {
+            binding.modifiers == modifiers
+                && match binding.input {
+                    BindingInput::Key(key) => *self.focus == focus && self.keys.just_pressed(key),
+                    BindingInput::Mouse(button) => self.mouse.just_pressed(button),
+                }
+        })
+    }
+
+    /// Returns `true` if the main input of any binding is held down.
+    pub fn pressed(&self, bindings: &[Binding]) -> bool {
+        bindings.iter().any(|binding| match binding.input {
+            BindingInput::Key(key) => *self.focus == EditorFocus::Canvas && self.keys.pressed(key),
+            BindingInput::Mouse(button) => self.mouse.pressed(button),
+        })
+    }
+
+    /// Returns `true` if the main input of any binding was released this frame.
+    pub fn just_released(&self, bindings: &[Binding]) -> bool {
+        bindings.iter().any(|binding| match binding.input {
+            BindingInput::Key(key) => {
+                *self.focus == EditorFocus::Canvas && self.keys.just_released(key)
+            }
+            BindingInput::Mouse(button) => self.mouse.just_released(button),
+        })
+    }
+}
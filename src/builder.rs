@@ -0,0 +1,157 @@
+#![allow(clippy::type_complexity)]
+
+use bevy::prelude::*;
+use std::marker::PhantomData;
+
+use crate::{
+    connection::{complete_partial_connection, convert_partial_connection, PartialConnection},
+    editor::EditorSet,
+    node::{FlowNode, FlowNodeInput, FlowNodeOutput},
+    node_set::FlowNodeSet,
+    template::{build_node, FlowNodeTemplate},
+};
+
+/// Connects nodes spawned through [`FlowNodeCommands`] once they have been built.
+pub struct BuilderPlugin<N: FlowNodeSet>(PhantomData<N>);
+
+impl<N: FlowNodeSet> Default for BuilderPlugin<N> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<N: FlowNodeSet> Plugin for BuilderPlugin<N> {
+    fn build(&self, app: &mut App) {
+        // Nodes, their ports and their wires are all built in the frame they are spawned.
+        app.add_systems(
+            Update,
+            (apply_deferred, connect_nodes::<N>, apply_deferred)
+                .chain()
+                .after(build_node::<N>)
+                .after(complete_partial_connection::<N>)
+                .before(convert_partial_connection::<N>)
+                .in_set(EditorSet::Layout),
+        );
+    }
+}
+
+/// A node spawned through [`FlowNodeCommands`], usable before the node has been built.
+pub struct NodeHandle<N: FlowNodeSet> {
+    pub entity: Entity,
+    _phantom: PhantomData<N>,
+}
+
+impl<N: FlowNodeSet> Clone for NodeHandle<N> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<N: FlowNodeSet> Copy for NodeHandle<N> {}
+
+impl<N: FlowNodeSet> NodeHandle<N> {
+    pub fn new(entity: Entity) -> Self {
+        Self {
+            entity,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+/// Builds graphs from code.
+///
+/// Nodes spawned before `Update`, or by systems running before [`EditorSet::Layout`], are built
+/// and connected within the same frame.
+pub trait FlowNodeCommands {
+    /// Connects the output labelled `output` of `from` to the input labelled `input` of `to`,
+    /// replacing any wire already plugged into the input.
+    fn connect<N: FlowNodeSet>(
+        &mut self,
+        from: NodeHandle<N>,
+        output: &str,
+        to: NodeHandle<N>,
+        input: &str,
+    );
+    /// Spawns the template of `node` at `position`.
+    fn spawn_node<N: FlowNodeSet>(&mut self, node: N, position: Vec2) -> NodeHandle<N>;
+    fn spawn_template<N: FlowNodeSet>(&mut self, template: FlowNodeTemplate<N>) -> NodeHandle<N>;
+}
+
+impl FlowNodeCommands for Commands<'_, '_> {
+    fn connect<N: FlowNodeSet>(
+        &mut self,
+        from: NodeHandle<N>,
+        output: &str,
+        to: NodeHandle<N>,
+        input: &str,
+    ) {
+        self.spawn(PendingConnection::<N> {
+            from: from.entity,
+            input: input.to_string(),
+            output: output.to_string(),
+            to: to.entity,
+            _phantom: PhantomData,
+        });
+    }
+
+    fn spawn_node<N: FlowNodeSet>(&mut self, node: N, position: Vec2) -> NodeHandle<N> {
+        self.spawn_template(FlowNodeTemplate {
+            position,
+            ..node.template()
+        })
+    }
+
+    fn spawn_template<N: FlowNodeSet>(&mut self, template: FlowNodeTemplate<N>) -> NodeHandle<N> {
+        NodeHandle::new(self.spawn(template).id())
+    }
+}
+
+#[derive(Component)]
+struct PendingConnection<N: FlowNodeSet> {
+    from: Entity,
+    input: String,
+    output: String,
+    to: Entity,
+    _phantom: PhantomData<N>,
+}
+
+fn connect_nodes<N: FlowNodeSet>(
+    mut commands: Commands,
+    q_pending: Query<(Entity, &PendingConnection<N>)>,
+    q_nodes: Query<Option<&Children>, Or<(With<FlowNode<N>>, With<FlowNodeTemplate<N>>)>>,
+    q_inputs: Query<&FlowNodeInput<N>>,
+    q_outputs: Query<&FlowNodeOutput>,
+) {
+    for (entity, pending) in q_pending.iter() {
+        let (Ok(from), Ok(to)) = (q_nodes.get(pending.from), q_nodes.get(pending.to)) else {
+            warn!("Connected nodes no longer exist");
+            commands.entity(entity).despawn();
+            continue;
+        };
+        // Templates are waiting for their editor.
+        let (Some(from), Some(to)) = (from, to) else {
+            continue;
+        };
+
+        let output = from.iter().find(|child| {
+            q_outputs
+                .get(**child)
+                .is_ok_and(|o| o.label == pending.output)
+        });
+        let input = to.iter().find(|child| {
+            q_inputs
+                .get(**child)
+                .is_ok_and(|i| i.label == pending.input)
+        });
+
+        match (input, output) {
+            (Some(input), Some(output)) => {
+                commands.spawn(PartialConnection::<N>::new(Some(*input), Some(*output)));
+            }
+            (None, _) => warn!("Node has no input labelled {:?}", pending.input),
+            (_, None) => warn!("Node has no output labelled {:?}", pending.output),
+        }
+
+        commands.entity(entity).despawn();
+    }
+}
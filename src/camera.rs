@@ -1,51 +1,291 @@
-use bevy::{
-    input::mouse::MouseWheel,
-    prelude::*,
-    render::camera::ScalingMode,
-    window::PrimaryWindow,
-};
+use bevy::{input::mouse::MouseWheel, prelude::*};
 
-use crate::cursor::CursorCamera;
+use crate::{
+    bindings::{BindingInputs, FlowNodeBindings},
+    cursor::{screen_to_world, world_per_pixel, CursorCamera, CursorPosition},
+    editor::{editor_editable, EditorSet, InEditor},
+    focus::EditorFocus,
+    interactions::{Clickable, Clicked},
+    node::{ActiveNode, Selected},
+};
 
 pub struct PanCameraPlugin;
 
 impl Plugin for PanCameraPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, setup)
-            .add_systems(Update, (move_camera, zoom_camera));
+        app.init_resource::<FlowNodeBindings>()
+            .init_resource::<EditorFocus>()
+            .init_resource::<PanCameraConfig>()
+            .add_systems(Startup, setup)
+            .add_systems(
+                Update,
+                (
+                    animate_camera.after(frame_camera),
+                    // Nothing can be dragged in a read only editor.
+                    edge_pan_camera.run_if(editor_editable),
+                    frame_camera,
+                    key_pan_camera,
+                    move_camera,
+                    zoom_camera,
+                )
+                    .in_set(EditorSet::Interaction),
+            );
+    }
+}
+
+#[derive(Resource)]
+pub struct PanCameraConfig {
+    /// Distance from the border of the editor at which dragging starts panning, in pixels.
+    pub edge_pan_margin: f32,
+    /// In pixels per second.
+    pub edge_pan_speed: f32,
+    /// Space kept around framed nodes, in pixels.
+    pub frame_padding: f32,
+    /// How quickly the camera approaches a [`CameraTarget`], higher is faster.
+    pub frame_speed: f32,
+    pub max_scale: f32,
+    pub min_scale: f32,
+    /// In pixels per second.
+    pub pan_speed: f32,
+    pub zoom_speed: f32,
+}
+
+impl Default for PanCameraConfig {
+    fn default() -> Self {
+        Self {
+            edge_pan_margin: 30.0,
+            edge_pan_speed: 600.0,
+            frame_padding: 50.0,
+            frame_speed: 10.0,
+            max_scale: 10.0,
+            min_scale: 0.1,
+            pan_speed: 600.0,
+            zoom_speed: 0.25,
+        }
     }
 }
 
 #[derive(Component)]
 pub struct PanCamera;
 
+/// Where a [`PanCamera`] is moving to, removed once it arrives or is moved by hand.
+#[derive(Clone, Copy, Component, Debug)]
+pub struct CameraTarget {
+    pub scale: f32,
+    pub translation: Vec2,
+}
+
 fn setup(mut commands: Commands) {
     commands.spawn((Camera2dBundle::default(), CursorCamera, PanCamera));
 }
 
+fn animate_camera(
+    mut commands: Commands,
+    config: Res<PanCameraConfig>,
+    time: Res<Time>,
+    mut query: Query<(
+        Entity,
+        &CameraTarget,
+        &mut Transform,
+        &mut OrthographicProjection,
+    )>,
+) {
+    let t = 1.0 - (-config.frame_speed * time.delta_seconds()).exp();
+
+    for (entity, target, mut transform, mut projection) in query.iter_mut() {
+        let translation = transform.translation.truncate().lerp(target.translation, t);
+        let scale = projection.scale + (target.scale - projection.scale) * t;
+        let arrived = translation.distance(target.translation) < 0.5 * scale
+            && (scale - target.scale).abs() < 0.001 * target.scale;
+
+        if arrived {
+            transform.translation = target.translation.extend(transform.translation.z);
+            projection.scale = target.scale;
+            commands.entity(entity).remove::<CameraTarget>();
+        } else {
+            transform.translation = translation.extend(transform.translation.z);
+            projection.scale = scale;
+        }
+    }
+}
+
+fn edge_pan_camera(
+    mut commands: Commands,
+    bindings: Res<FlowNodeBindings>,
+    config: Res<PanCameraConfig>,
+    cursor: Res<CursorPosition>,
+    input: BindingInputs,
+    time: Res<Time>,
+    mut ev_click: EventReader<Clicked>,
+    mut query: Query<(Entity, &Camera, &mut Transform, &OrthographicProjection), With<PanCamera>>,
+    mut dragging: Local<bool>,
+) {
+    // Anything picked up on the canvas, a node or a wire, can be dragged towards the border.
+    if ev_click.read().any(|ev| ev.0.is_some()) {
+        *dragging = true;
+    }
+
+    if !input.pressed(&bindings.select) {
+        *dragging = false;
+    }
+
+    if !*dragging {
+        return;
+    }
+
+    let rect = cursor.editor_rect;
+    let screen = Vec2::new(cursor.screen_x, cursor.screen_y);
+    let closeness = |distance: f32| {
+        ((config.edge_pan_margin - distance) / config.edge_pan_margin).clamp(0.0, 1.0)
+    };
+    let direction = Vec2::new(
+        closeness(rect.max.x - screen.x) - closeness(screen.x - rect.min.x),
+        closeness(screen.y - rect.min.y) - closeness(rect.max.y - screen.y),
+    );
+
+    if direction == Vec2::ZERO {
+        return;
+    }
+
+    for (entity, camera, mut transform, projection) in query
+        .iter_mut()
+        .filter(|(entity, _, _, _)| Some(*entity) == cursor.editor)
+    {
+        let delta = direction
+            * config.edge_pan_speed
+            * world_per_pixel(camera, projection)
+            * time.delta_seconds();
+
+        transform.translation += delta.extend(0.0);
+        commands.entity(entity).remove::<CameraTarget>();
+    }
+}
+
+fn frame_camera(
+    mut commands: Commands,
+    bindings: Res<FlowNodeBindings>,
+    config: Res<PanCameraConfig>,
+    cursor: Res<CursorPosition>,
+    input: BindingInputs,
+    q_cameras: Query<(Entity, &Camera, &OrthographicProjection, &ActiveNode), With<PanCamera>>,
+    q_nodes: Query<
+        (
+            Entity,
+            &Clickable,
+            &GlobalTransform,
+            &InEditor,
+            Has<Selected>,
+        ),
+        Without<Parent>,
+    >,
+) {
+    let selected = input.just_pressed(&bindings.frame_selected);
+
+    if !selected && !input.just_pressed(&bindings.frame_all) {
+        return;
+    }
+
+    let Some((editor, camera, projection, active_node)) =
+        cursor.editor.and_then(|e| q_cameras.get(e).ok())
+    else {
+        return;
+    };
+    // The selection is the active node along with the selected nodes.
+    let bounds = q_nodes
+        .iter()
+        .filter(|(entity, _, _, in_editor, is_selected)| {
+            in_editor.0 == editor
+                && (!selected || *is_selected || active_node.entity == Some(*entity))
+        })
+        .filter_map(|(_, clickable, transform, ..)| match clickable {
+            Clickable::Area(size) => Some(Rect::from_center_size(
+                transform.translation().truncate(),
+                *size,
+            )),
+            _ => None,
+        })
+        .reduce(|a, b| a.union(b));
+    let (Some(bounds), Some(viewport_size)) = (bounds, camera.logical_viewport_size()) else {
+        return;
+    };
+
+    // The area seen at a scale of one, and the share of the viewport left inside the padding.
+    let unit_area = projection.area.size() / projection.scale;
+    let fill = (Vec2::ONE - 2.0 * config.frame_padding / viewport_size).max(Vec2::splat(0.1));
+    let scale = (bounds.size() / (unit_area * fill))
+        .max_element()
+        .clamp(config.min_scale, config.max_scale);
+
+    commands.entity(editor).insert(CameraTarget {
+        scale,
+        translation: bounds.center(),
+    });
+}
+
+fn key_pan_camera(
+    mut commands: Commands,
+    bindings: Res<FlowNodeBindings>,
+    config: Res<PanCameraConfig>,
+    cursor: Res<CursorPosition>,
+    input: BindingInputs,
+    time: Res<Time>,
+    mut query: Query<(Entity, &Camera, &mut Transform, &OrthographicProjection), With<PanCamera>>,
+) {
+    let axis = |bindings: &[_]| if input.pressed(bindings) { 1.0 } else { 0.0 };
+    let direction = Vec2::new(
+        axis(&bindings.pan_right) - axis(&bindings.pan_left),
+        axis(&bindings.pan_up) - axis(&bindings.pan_down),
+    );
+
+    if direction == Vec2::ZERO {
+        return;
+    }
+
+    for (entity, camera, mut transform, projection) in query
+        .iter_mut()
+        .filter(|(entity, _, _, _)| Some(*entity) == cursor.editor)
+    {
+        let delta = direction.normalize()
+            * config.pan_speed
+            * world_per_pixel(camera, projection)
+            * time.delta_seconds();
+
+        transform.translation += delta.extend(0.0);
+        commands.entity(entity).remove::<CameraTarget>();
+    }
+}
+
 fn move_camera(
-    mouse: Res<Input<MouseButton>>,
-    q_window: Query<&Window, With<PrimaryWindow>>,
-    mut query: Query<(&mut Transform, &OrthographicProjection), With<PanCamera>>,
+    mut commands: Commands,
+    bindings: Res<FlowNodeBindings>,
+    cursor: Res<CursorPosition>,
+    input: BindingInputs,
+    mut query: Query<(Entity, &Camera, &mut Transform, &GlobalTransform), With<PanCamera>>,
     mut previous_pos: Local<Option<Vec2>>,
+    mut panning: Local<bool>,
 ) {
-    let window = q_window.get_single().unwrap();
-    let window_size = Vec2::new(window.width(), window.height());
-    let current_pos = match window.cursor_position() {
-        Some(pos) => pos,
-        None => return,
-    };
-    let delta_pos = current_pos - previous_pos.unwrap_or(current_pos);
+    if input.just_pressed(&bindings.pan_camera) {
+        *panning = true;
+    } else if !input.pressed(&bindings.pan_camera) {
+        *panning = false;
+    }
 
-    if mouse.pressed(MouseButton::Middle) {
-        for (mut transform, projection) in query.iter_mut() {
-            if let ScalingMode::Fixed { width, height } = projection.scaling_mode {
-                let proj_size = Vec2::new(width, height) * projection.scale;
-                let world_pos_ratio = proj_size / window_size;
-                let delta_world = delta_pos * world_pos_ratio;
+    let current_pos = Vec2::new(cursor.screen_x, cursor.screen_y);
 
-                transform.translation -= delta_world.extend(0.0);
+    if let (true, Some(previous_pos)) = (*panning, *previous_pos) {
+        for (entity, camera, mut transform, camera_transform) in query
+            .iter_mut()
+            .filter(|(entity, _, _, _)| Some(*entity) == cursor.editor)
+        {
+            // Keeps the point grabbed on the canvas under the cursor.
+            let to_world =
+                |position| screen_to_world(camera, camera_transform, cursor.editor_rect, position);
+
+            if let (Some(from), Some(to)) = (to_world(previous_pos), to_world(current_pos)) {
+                transform.translation += (from - to).extend(0.0);
             }
+
+            commands.entity(entity).remove::<CameraTarget>();
         }
     }
 
@@ -53,8 +293,11 @@ fn move_camera(
 }
 
 fn zoom_camera(
+    mut commands: Commands,
+    config: Res<PanCameraConfig>,
+    cursor: Res<CursorPosition>,
     mut ev_scroll: EventReader<MouseWheel>,
-    mut query: Query<&mut OrthographicProjection, With<PanCamera>>,
+    mut query: Query<(Entity, &mut Transform, &mut OrthographicProjection), With<PanCamera>>,
 ) {
     let scroll = ev_scroll.read().map(|ev| ev.y).sum::<f32>();
 
@@ -62,10 +305,178 @@ fn zoom_camera(
         return;
     }
 
-    for mut projection in query.iter_mut() {
-        let mut log_scale = projection.scale.ln();
+    for (entity, mut transform, mut projection) in query
+        .iter_mut()
+        .filter(|(entity, _, _)| Some(*entity) == cursor.editor)
+    {
+        let scale = (projection.scale.ln() - scroll * config.zoom_speed)
+            .exp()
+            .clamp(config.min_scale, config.max_scale);
+        // Keeps the point under the cursor in place.
+        let anchor = cursor.position();
+        let translation =
+            anchor + (transform.translation.truncate() - anchor) * (scale / projection.scale);
+
+        transform.translation = translation.extend(transform.translation.z);
+        projection.scale = scale;
+        commands.entity(entity).remove::<CameraTarget>();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::input::mouse::MouseScrollUnit;
+
+    use super::*;
+    use crate::cursor::tests::{assert_near, editor_app};
+
+    fn pan_app() -> (App, Entity) {
+        let (mut app, editor) = editor_app();
+
+        app.init_resource::<EditorFocus>()
+            .init_resource::<FlowNodeBindings>()
+            .init_resource::<PanCameraConfig>()
+            .add_event::<MouseWheel>()
+            .add_systems(Update, (move_camera, zoom_camera));
+        app.world.entity_mut(editor).insert(PanCamera);
+
+        let rect = app
+            .world
+            .get::<Camera>(editor)
+            .unwrap()
+            .logical_viewport_rect()
+            .unwrap();
+
+        app.insert_resource(CursorPosition {
+            editor: Some(editor),
+            editor_rect: rect,
+            ..default()
+        });
+
+        (app, editor)
+    }
+
+    fn move_cursor(app: &mut App, screen: Vec2, world: Vec2) {
+        let mut cursor = app.world.resource_mut::<CursorPosition>();
+
+        cursor.screen_x = screen.x;
+        cursor.screen_y = screen.y;
+        cursor.x = world.x;
+        cursor.y = world.y;
+    }
+
+    fn world_under(app: &mut App, editor: Entity, screen: Vec2) -> Vec2 {
+        let rect = app.world.resource::<CursorPosition>().editor_rect;
+        let (camera, transform) = app
+            .world
+            .query::<(&Camera, &GlobalTransform)>()
+            .get(&app.world, editor)
+            .unwrap();
+
+        screen_to_world(camera, transform, rect, screen).unwrap()
+    }
+
+    #[test]
+    fn pan_keeps_grabbed_point_under_cursor() {
+        let (mut app, editor) = pan_app();
+        let grabbed = Vec2::new(250.0, 200.0);
+        let released = Vec2::new(280.0, 180.0);
+
+        move_cursor(&mut app, grabbed, Vec2::ZERO);
+        app.update();
+        app.world
+            .resource_mut::<Input<MouseButton>>()
+            .press(MouseButton::Middle);
+        app.update();
+        app.world.resource_mut::<Input<MouseButton>>().clear();
+        move_cursor(&mut app, released, Vec2::ZERO);
+        app.update();
+
+        let translation = app.world.get::<Transform>(editor).unwrap().translation;
+
+        // Half a world unit per logical pixel, with y pointing up.
+        assert_near(translation.truncate(), Vec2::new(-15.0, -10.0));
+        assert_near(world_under(&mut app, editor, released), Vec2::ZERO);
+    }
+
+    #[test]
+    fn frame_selected_covers_selected_nodes() {
+        let (mut app, editor) = pan_app();
+
+        app.add_systems(Update, frame_camera);
+        app.world.entity_mut(editor).insert(ActiveNode::default());
+
+        let mut spawn = |position: Vec2, selected: bool| {
+            let mut node = app.world.spawn((
+                Clickable::Area(Vec2::new(20.0, 10.0)),
+                GlobalTransform::from_translation(position.extend(0.0)),
+                InEditor(editor),
+            ));
+
+            if selected {
+                node.insert(Selected);
+            }
+
+            node.id()
+        };
+
+        spawn(Vec2::new(-100.0, 0.0), true);
+        spawn(Vec2::new(100.0, 0.0), true);
+        spawn(Vec2::new(0.0, 300.0), false);
+
+        let active = spawn(Vec2::new(0.0, -300.0), false);
+        let frame = |app: &mut App| {
+            app.world.resource_mut::<Input<KeyCode>>().press(KeyCode::F);
+            app.update();
+            app.world.resource_mut::<Input<KeyCode>>().reset(KeyCode::F);
+
+            app.world.get::<CameraTarget>(editor).unwrap().translation
+        };
+
+        assert_near(frame(&mut app), Vec2::ZERO);
+
+        // Falls back to the active node once nothing is selected.
+        let selected = app
+            .world
+            .query_filtered::<Entity, With<Selected>>()
+            .iter(&app.world)
+            .collect::<Vec<_>>();
+
+        for entity in selected {
+            app.world.entity_mut(entity).remove::<Selected>();
+        }
+
+        app.world.get_mut::<ActiveNode>(editor).unwrap().entity = Some(active);
+
+        assert_near(frame(&mut app), Vec2::new(0.0, -300.0));
+    }
+
+    #[test]
+    fn zoom_keeps_point_under_cursor() {
+        let (mut app, editor) = pan_app();
+        let screen = Vec2::new(310.0, 240.0);
+        let world = Vec2::new(30.0, -20.0);
+        let window = app
+            .world
+            .query_filtered::<Entity, With<Window>>()
+            .single(&app.world);
+
+        move_cursor(&mut app, screen, world);
+        app.world.send_event(MouseWheel {
+            unit: MouseScrollUnit::Line,
+            x: 0.0,
+            y: 1.0,
+            window,
+        });
+        app.update();
+
+        let scale = app
+            .world
+            .get::<OrthographicProjection>(editor)
+            .unwrap()
+            .scale;
 
-        log_scale -= scroll * 0.25;
-        projection.scale = log_scale.exp();
+        assert!(scale < 1.0);
+        assert_near(world_under(&mut app, editor, screen), world);
     }
 }
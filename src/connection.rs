@@ -5,30 +5,55 @@ use bevy_prototype_lyon::{entity::ShapeBundle, prelude::*};
 use std::marker::PhantomData;
 
 use crate::{
+    bindings::{BindingInputs, FlowNodeBindings},
     cursor::CursorPosition,
+    editor::{editor_editable, EditorSet, InEditor},
+    focus::EditorFocus,
     interactions::Clicked,
-    node::{FlowNodeInput, FlowNodeOutput, FlowNodeResources, FlowNodeSet},
+    node::{FlowNode, FlowNodeInput, FlowNodeOutput, InputMaterials, Locked, PortStyle},
+    node_set::FlowNodeSet,
+    notice::ActionRejected,
+    theme::{mix, FlowNodeTheme},
 };
 
+// Wires between ports of different colours are split into segments so they can fade from one
+// colour to the other.
+const CONNECTION_SEGMENTS: usize = 12;
+const SEGMENT_SAMPLES: usize = 4;
+
 #[derive(Default)]
 pub struct ConnectionPlugin<N: FlowNodeSet>(PhantomData<N>);
 
 impl<N: FlowNodeSet> Plugin for ConnectionPlugin<N> {
     fn build(&self, app: &mut App) {
-        app.insert_resource(ConnectionConfig::default())
+        app.init_resource::<FlowNodeBindings>()
+            .init_resource::<EditorFocus>()
+            .init_resource::<FlowNodeTheme>()
+            .init_resource::<ConnectionConfig>()
             .add_event::<ConnectionEvent>()
-            .add_plugins(ShapePlugin)
             .add_systems(
                 Update,
                 (
-                    break_connection::<N>,
-                    draw_connections::<N>,
-                    draw_partial_connections::<N>,
-                    complete_partial_connection::<N>,
-                    convert_partial_connection::<N>,
-                    create_partial_connection::<N>.before(break_connection::<N>),
+                    (
+                        break_connection::<N>,
+                        complete_partial_connection::<N>,
+                        create_partial_connection::<N>.before(break_connection::<N>),
+                    )
+                        .run_if(editor_editable)
+                        .in_set(EditorSet::Interaction),
+                    (
+                        cancel_partial_connections::<N>.run_if(not(editor_editable)),
+                        convert_partial_connection::<N>,
+                    )
+                        .in_set(EditorSet::Layout),
+                    (draw_connections::<N>, draw_partial_connections::<N>)
+                        .in_set(EditorSet::Render),
                 ),
             );
+
+        if !app.is_plugin_added::<ShapePlugin>() {
+            app.add_plugins(ShapePlugin);
+        }
     }
 }
 
@@ -47,45 +72,151 @@ impl Default for ConnectionConfig {
     }
 }
 
-#[derive(Event)]
+#[derive(Clone, Debug, Event)]
 pub enum ConnectionEvent {
-    Propagate,
-    Created,
-    Destroyed,
+    /// The value of a node changed, so everything downstream of it has to be resolved again.
+    Propagate(Entity),
+    Created(ConnectionInfo),
+    Destroyed(ConnectionInfo),
+}
+
+/// The ports joined by a wire and the nodes they belong to.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConnectionInfo {
+    pub input: Entity,
+    pub input_label: String,
+    pub input_node: Entity,
+    pub output: Entity,
+    pub output_label: String,
+    pub output_node: Entity,
+}
+
+impl ConnectionInfo {
+    pub(crate) fn new<N: FlowNodeSet>(
+        (input, input_node, node_input): (Entity, Entity, &FlowNodeInput<N>),
+        (output, output_node, node_output): (Entity, Entity, &FlowNodeOutput),
+    ) -> Self {
+        Self {
+            input,
+            input_label: node_input.label.clone(),
+            input_node,
+            output,
+            output_label: node_output.label.clone(),
+            output_node,
+        }
+    }
 }
 
 #[derive(Component)]
-struct PartialConnection {
+pub(crate) struct PartialConnection<N: FlowNodeSet> {
     input: Option<Entity>,
     output: Option<Entity>,
+    phantom: PhantomData<N>,
+}
+
+impl<N: FlowNodeSet> PartialConnection<N> {
+    pub(crate) fn new(input: Option<Entity>, output: Option<Entity>) -> Self {
+        Self {
+            input,
+            output,
+            phantom: PhantomData,
+        }
+    }
 }
 
 #[derive(Component)]
-struct Connection;
+pub(crate) struct Connection;
+
+#[derive(Component)]
+struct ConnectionSegment(usize);
+
+fn port_color(style: &PortStyle, theme: &FlowNodeTheme) -> Color {
+    style.color.unwrap_or(theme.connection)
+}
+
+fn bezier(start: Vec2, end: Vec2, t: f32) -> Vec2 {
+    let half_x = (end.x - start.x) / 2.0;
+    let ctrl_1 = Vec2::new(start.x + half_x, start.y);
+    let ctrl_2 = Vec2::new(start.x + half_x, end.y);
+    let u = 1.0 - t;
 
+    start * u * u * u + ctrl_1 * 3.0 * u * u * t + ctrl_2 * 3.0 * u * t * t + end * t * t * t
+}
+
+// The part of the wire from `start` to `end` between `t_start` and `t_end`, as `samples` lines.
+fn wire_path(start: Vec2, end: Vec2, t_start: f32, t_end: f32, samples: usize) -> Path {
+    let mut path_builder = PathBuilder::new();
+    let t_step = (t_end - t_start) / samples as f32;
+
+    path_builder.move_to(bezier(start, end, t_start));
+
+    for sample in 1..=samples {
+        path_builder.line_to(bezier(start, end, t_start + sample as f32 * t_step));
+    }
+
+    ShapePath::build_as(&path_builder.build())
+}
+
+// Drops the wires the user was drawing when the editor stops being editable, wires built from
+// code have no stroke.
+fn cancel_partial_connections<N: FlowNodeSet>(
+    mut commands: Commands,
+    q_connections: Query<Entity, (With<PartialConnection<N>>, With<Stroke>)>,
+) {
+    for entity in q_connections.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 fn break_connection<N: FlowNodeSet>(
     mut commands: Commands,
     config: Res<ConnectionConfig>,
-    node_res: Res<FlowNodeResources>,
+    theme: Res<FlowNodeTheme>,
     mut ev_click: EventReader<Clicked>,
     mut ev_connection: EventWriter<ConnectionEvent>,
+    mut ev_rejected: EventWriter<ActionRejected>,
     q_connection: Query<(Entity, &Parent), With<Connection>>,
-    mut q_inputs: Query<&mut FlowNodeInput<N>>,
+    q_nodes: Query<(&FlowNode<N>, Has<Locked>)>,
+    mut q_inputs: Query<(
+        &Parent,
+        &mut FlowNodeInput<N>,
+        &InputMaterials,
+        Option<&InEditor>,
+    )>,
+    q_outputs: Query<(&Parent, &FlowNodeOutput)>,
     mut q_material: Query<(&Parent, &mut Handle<ColorMaterial>)>,
 ) {
     for ev in ev_click.read() {
         if let Clicked(Some(entity)) = ev {
-            if let Ok(mut node_input) = q_inputs.get_mut(*entity) {
-                if node_input.connection.is_some() {
-                    commands.spawn((
-                        PartialConnection {
-                            input: None,
-                            output: node_input.connection,
-                        },
+            if let Ok((input_parent, mut node_input, input_materials, editor)) =
+                q_inputs.get_mut(*entity)
+            {
+                if let Some(output) = node_input.connection {
+                    if let Some(Err(reason)) = q_outputs.get(output).ok().and_then(|(parent, o)| {
+                        let (from, from_locked) = q_nodes.get(parent.get()).ok()?;
+                        let (to, to_locked) = q_nodes.get(input_parent.get()).ok()?;
+
+                        if from_locked || to_locked {
+                            return Some(Err("This node is locked".to_string()));
+                        }
+
+                        Some(from.can_disconnect(&o.label, to, &node_input.label))
+                    }) {
+                        ev_rejected.send(ActionRejected { reason });
+                        continue;
+                    }
+
+                    let mut partial = commands.spawn((
+                        PartialConnection::<N>::new(None, node_input.connection),
                         ShapeBundle::default(),
-                        Stroke::new(Color::WHITE, config.connection_size),
+                        Stroke::new(theme.connection, config.connection_size),
                     ));
 
+                    if let Some(editor) = editor {
+                        partial.insert(*editor);
+                    }
+
                     for (connection_entity, parent) in q_connection.iter() {
                         if parent.get() == *entity {
                             commands.entity(connection_entity).despawn_recursive();
@@ -93,12 +224,18 @@ fn break_connection<N: FlowNodeSet>(
                         }
                     }
 
+                    if let Ok((output_parent, node_output)) = q_outputs.get(output) {
+                        ev_connection.send(ConnectionEvent::Destroyed(ConnectionInfo::new(
+                            (*entity, input_parent.get(), &*node_input),
+                            (output, output_parent.get(), node_output),
+                        )));
+                    }
+
                     node_input.connection = None;
-                    ev_connection.send(ConnectionEvent::Destroyed);
 
                     for (parent, mut material) in q_material.iter_mut() {
                         if parent.get() == *entity {
-                            *material = node_res.material_handle_input_inactive.clone();
+                            *material = input_materials.inactive.clone();
                         }
                     }
                 }
@@ -107,19 +244,25 @@ fn break_connection<N: FlowNodeSet>(
     }
 }
 
-fn complete_partial_connection<T: FlowNodeSet>(
+pub(crate) fn complete_partial_connection<T: FlowNodeSet>(
     mut commands: Commands,
+    bindings: Res<FlowNodeBindings>,
     config: Res<ConnectionConfig>,
     cursor: Res<CursorPosition>,
-    mouse_button_input: Res<Input<MouseButton>>,
-    mut q_connections: Query<(Entity, &mut PartialConnection)>,
-    q_input: Query<(Entity, &GlobalTransform), With<FlowNodeInput<T>>>,
-    q_output: Query<(Entity, &GlobalTransform), With<FlowNodeOutput>>,
+    input: BindingInputs,
+    mut q_connections: Query<(Entity, &mut PartialConnection<T>, Option<&InEditor>)>,
+    q_input: Query<(Entity, &GlobalTransform, Option<&InEditor>), With<FlowNodeInput<T>>>,
+    q_output: Query<(Entity, &Parent, &GlobalTransform, Option<&InEditor>), With<FlowNodeOutput>>,
+    q_nodes: Query<(), With<FlowNode<T>>>,
 ) {
-    if mouse_button_input.just_released(MouseButton::Left) {
-        for (entity, mut connection) in q_connections.iter_mut() {
+    if input.just_released(&bindings.select) {
+        for (entity, mut connection, editor) in q_connections.iter_mut() {
             if connection.input.is_some() {
-                for (entity, transform) in q_output.iter() {
+                for (entity, _, transform, _) in
+                    q_output.iter().filter(|(_, parent, _, port_editor)| {
+                        *port_editor == editor && q_nodes.contains(parent.get())
+                    })
+                {
                     let translation = transform.translation();
 
                     if (translation.x - cursor.x).abs() < config.connection_threshold
@@ -130,7 +273,10 @@ fn complete_partial_connection<T: FlowNodeSet>(
                     }
                 }
             } else if connection.output.is_some() {
-                for (entity, transform) in q_input.iter() {
+                for (entity, transform, _) in q_input
+                    .iter()
+                    .filter(|(_, _, port_editor)| *port_editor == editor)
+                {
                     let translation = transform.translation();
 
                     if (translation.x - cursor.x).abs() < config.connection_threshold
@@ -149,51 +295,119 @@ fn complete_partial_connection<T: FlowNodeSet>(
     }
 }
 
-fn convert_partial_connection<N: FlowNodeSet>(
+pub(crate) fn convert_partial_connection<N: FlowNodeSet>(
     mut commands: Commands,
-    config: Res<ConnectionConfig>,
-    node_res: Res<FlowNodeResources>,
     mut ev_connection: EventWriter<ConnectionEvent>,
-    q_connections: Query<(Entity, &PartialConnection)>,
-    q_outputs: Query<&Parent, With<FlowNodeOutput>>,
-    mut q_inputs: Query<(Entity, &Parent, &GlobalTransform, &mut FlowNodeInput<N>)>,
+    mut ev_rejected: EventWriter<ActionRejected>,
+    q_connections: Query<(Entity, &PartialConnection<N>, Has<Stroke>)>,
+    q_nodes: Query<(&FlowNode<N>, Has<Locked>)>,
+    q_wires: Query<(Entity, &Parent), With<Connection>>,
+    q_outputs: Query<(&Parent, &FlowNodeOutput)>,
+    mut q_inputs: Query<(
+        Entity,
+        &Parent,
+        &GlobalTransform,
+        &mut FlowNodeInput<N>,
+        &InputMaterials,
+    )>,
     mut q_material: Query<(&Parent, &mut Handle<ColorMaterial>)>,
 ) {
-    for (entity, connection) in q_connections.iter() {
+    // Wires drawn by the user have a stroke, wires built from code don't.
+    for (entity, connection, drawn) in q_connections.iter() {
         if let Some(input) = connection.input {
             if let Some(output) = connection.output {
-                if let Ok((input_entity, input_parent, &transform, mut input)) =
+                if let Ok((input_entity, input_parent, &transform, mut input, input_materials)) =
                     q_inputs.get_mut(input)
                 {
-                    if let Ok(output_parent) = q_outputs.get(output) {
-                        if input_parent.get() != output_parent.get() {
+                    if let Ok((output_parent, node_output)) = q_outputs.get(output) {
+                        // Ports of different colours or shapes are wired with a gradient.
+                        let mismatch = if input_parent.get() == output_parent.get() {
+                            Some("A node can't be wired to itself")
+                        } else if input.kind != node_output.kind {
+                            Some("Execution ports only wire to execution ports")
+                        } else {
+                            None
+                        };
+                        let valid = mismatch.is_none();
+                        let to = q_nodes.get(input_parent.get()).ok();
+                        let connect = match (q_nodes.get(output_parent.get()), to) {
+                            (Ok((from, _)), Some((to, _))) => {
+                                from.can_connect(&node_output.label, to, &input.label)
+                            }
+                            _ => Ok(()),
+                        };
+                        // Dropping onto a wired input unplugs its wire.
+                        let unplug = input
+                            .connection
+                            .filter(|_| valid)
+                            .and_then(|previous| q_outputs.get(previous).ok())
+                            .and_then(|(previous_parent, previous_output)| {
+                                let (from, from_locked) =
+                                    q_nodes.get(previous_parent.get()).ok()?;
+                                let (to, to_locked) = to?;
+
+                                if drawn && (from_locked || to_locked) {
+                                    return Some(Err("This node is locked".to_string()));
+                                }
+
+                                Some(from.can_disconnect(&previous_output.label, to, &input.label))
+                            })
+                            .unwrap_or(Ok(()));
+
+                        if let Some(reason) = mismatch {
+                            ev_rejected.send(ActionRejected {
+                                reason: reason.to_string(),
+                            });
+                        } else if let Err(reason) = connect.and(unplug) {
+                            ev_rejected.send(ActionRejected { reason });
+                        } else {
+                            // Inputs built from code may already be connected.
+                            if let Some(previous) = input.connection {
+                                for (wire, parent) in q_wires.iter() {
+                                    if parent.get() == input_entity {
+                                        commands.entity(wire).despawn_recursive();
+                                    }
+                                }
+
+                                if let Ok((previous_parent, previous_output)) =
+                                    q_outputs.get(previous)
+                                {
+                                    ev_connection.send(ConnectionEvent::Destroyed(
+                                        ConnectionInfo::new(
+                                            (input_entity, input_parent.get(), &*input),
+                                            (previous, previous_parent.get(), previous_output),
+                                        ),
+                                    ));
+                                }
+                            }
+
                             input.connection = Some(output);
 
+                            // Its segments are spawned once it is drawn.
                             let child = commands
                                 .spawn((
-                                    ShapeBundle {
-                                        spatial: SpatialBundle {
-                                            transform: Transform::from_xyz(
-                                                0.0,
-                                                0.0,
-                                                -transform.translation().z,
-                                            ),
-                                            ..default()
-                                        },
+                                    SpatialBundle {
+                                        transform: Transform::from_xyz(
+                                            0.0,
+                                            0.0,
+                                            -transform.translation().z,
+                                        ),
                                         ..default()
                                     },
-                                    Stroke::new(Color::WHITE, config.connection_size),
                                     Connection,
                                 ))
                                 .id();
 
                             commands.entity(input_entity).push_children(&[child]);
 
-                            ev_connection.send(ConnectionEvent::Created);
+                            ev_connection.send(ConnectionEvent::Created(ConnectionInfo::new(
+                                (input_entity, input_parent.get(), &*input),
+                                (output, output_parent.get(), node_output),
+                            )));
 
                             for (parent, mut material) in q_material.iter_mut() {
                                 if parent.get() == input_entity {
-                                    *material = node_res.material_handle_input.clone();
+                                    *material = input_materials.active.clone();
                                 }
                             }
                         }
@@ -209,10 +423,13 @@ fn convert_partial_connection<N: FlowNodeSet>(
 fn create_partial_connection<N: FlowNodeSet>(
     mut commands: Commands,
     config: Res<ConnectionConfig>,
+    theme: Res<FlowNodeTheme>,
     mut ev_click: EventReader<Clicked>,
-    q_connections: Query<&PartialConnection>,
+    q_connections: Query<&PartialConnection<N>>,
+    q_editor: Query<&InEditor>,
     q_input: Query<&FlowNodeInput<N>>,
-    q_output: Query<&FlowNodeOutput>,
+    q_output: Query<&Parent, With<FlowNodeOutput>>,
+    q_nodes: Query<(), With<FlowNode<N>>>,
 ) {
     if !q_connections.is_empty() {
         return;
@@ -222,67 +439,125 @@ fn create_partial_connection<N: FlowNodeSet>(
         if let Clicked(Some(entity)) = ev {
             if let Ok(node_input) = q_input.get(*entity) {
                 if node_input.connection.is_none() {
-                    commands.spawn((
-                        PartialConnection {
-                            input: Some(*entity),
-                            output: None,
-                        },
+                    let mut partial = commands.spawn((
+                        PartialConnection::<N>::new(Some(*entity), None),
                         ShapeBundle::default(),
-                        Stroke::new(Color::WHITE, config.connection_size),
+                        Stroke::new(theme.connection, config.connection_size),
                     ));
+
+                    if let Ok(editor) = q_editor.get(*entity) {
+                        partial.insert(*editor);
+                    }
                 }
-            } else if q_output.get(*entity).is_ok() {
-                commands.spawn((
-                    PartialConnection {
-                        input: None,
-                        output: Some(*entity),
-                    },
+            } else if q_output
+                .get(*entity)
+                .is_ok_and(|parent| q_nodes.contains(parent.get()))
+            {
+                let mut partial = commands.spawn((
+                    PartialConnection::<N>::new(None, Some(*entity)),
                     ShapeBundle::default(),
-                    Stroke::new(Color::WHITE, config.connection_size),
+                    Stroke::new(theme.connection, config.connection_size),
                 ));
+
+                if let Ok(editor) = q_editor.get(*entity) {
+                    partial.insert(*editor);
+                }
             }
         }
     }
 }
 
+// Only rebuilds a wire when one of its ports moved or changed or the theme changed, as every
+// rebuilt segment is tessellated again.
 fn draw_connections<N: FlowNodeSet>(
     mut commands: Commands,
-    mut q_connection: Query<(Entity, &Parent, &mut Path), With<Connection>>,
-    mut q_input: Query<(&FlowNodeInput<N>, &GlobalTransform)>,
-    q_output: Query<&GlobalTransform, With<FlowNodeOutput>>,
+    config: Res<ConnectionConfig>,
+    theme: Res<FlowNodeTheme>,
+    q_connection: Query<(Entity, Ref<Connection>, &Parent, Option<&Children>)>,
+    mut q_segment: Query<(&ConnectionSegment, &mut Path, &mut Stroke)>,
+    q_input: Query<(Ref<FlowNodeInput<N>>, Ref<GlobalTransform>)>,
+    q_output: Query<(Ref<FlowNodeOutput>, Ref<GlobalTransform>)>,
 ) {
-    for (entity, parent, mut path) in q_connection.iter_mut() {
-        let mut cleanup = false;
-
-        if let Ok((input, input_transform)) = q_input.get_mut(parent.get()) {
-            if let Some(connection) = input.connection {
-                if let Ok(output_transform) = q_output.get(connection) {
-                    let mut path_builder = PathBuilder::new();
-                    let input_position = input_transform.translation().truncate();
-                    let output_position = output_transform.translation().truncate();
-                    let end = output_position - input_position;
-                    let half_x = end.x / 2.0;
-                    let ctrl_1 = Vec2::new(half_x, 0.0);
-                    let ctrl_2 = Vec2::new(half_x, end.y);
-
-                    path_builder.move_to(Vec2::ZERO);
-                    path_builder.cubic_bezier_to(ctrl_1, ctrl_2, end);
-
-                    let line = path_builder.build();
-
-                    *path = ShapePath::build_as(&line);
-                } else {
-                    cleanup = true;
-                }
-            } else {
-                cleanup = true;
-            }
+    for (entity, connection, parent, children) in q_connection.iter() {
+        let Some(((input, input_transform), (output, output_transform))) =
+            q_input.get(parent.get()).ok().and_then(|input| {
+                let output = q_output.get(input.0.connection?).ok()?;
+
+                Some((input, output))
+            })
+        else {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        };
+
+        let changed = theme.is_changed()
+            || connection.is_added()
+            || input.is_changed()
+            || input_transform.is_changed()
+            || output.is_changed()
+            || output_transform.is_changed();
+
+        if !changed {
+            continue;
+        }
+
+        let end =
+            output_transform.translation().truncate() - input_transform.translation().truncate();
+        let input_color = port_color(&input.style, &theme);
+        let output_color = port_color(&output.style, &theme);
+        // A wire between ports of the same colour is drawn as a single segment.
+        let segments = if input_color == output_color {
+            1
         } else {
-            cleanup = true;
+            CONNECTION_SEGMENTS
+        };
+        let samples = CONNECTION_SEGMENTS * SEGMENT_SAMPLES / segments;
+        let segment_path = |i: usize| {
+            let t_start = i as f32 / segments as f32;
+
+            wire_path(
+                Vec2::ZERO,
+                end,
+                t_start,
+                t_start + 1.0 / segments as f32,
+                samples,
+            )
+        };
+        let segment_color = |i: usize| {
+            mix(
+                input_color,
+                output_color,
+                (i as f32 + 0.5) / segments as f32,
+            )
+        };
+        let mut drawn = vec![false; segments];
+
+        for &child in children.into_iter().flatten() {
+            let Ok((segment, mut path, mut stroke)) = q_segment.get_mut(child) else {
+                continue;
+            };
+
+            if segment.0 >= segments {
+                commands.entity(child).despawn_recursive();
+                continue;
+            }
+
+            *path = segment_path(segment.0);
+            stroke.color = segment_color(segment.0);
+            drawn[segment.0] = true;
         }
 
-        if cleanup {
-            commands.entity(entity).despawn_recursive();
+        for i in (0..segments).filter(|&i| !drawn[i]) {
+            commands.entity(entity).with_children(|parent| {
+                parent.spawn((
+                    ShapeBundle {
+                        path: segment_path(i),
+                        ..default()
+                    },
+                    Stroke::new(segment_color(i), config.connection_size),
+                    ConnectionSegment(i),
+                ));
+            });
         }
     }
 }
@@ -290,10 +565,15 @@ fn draw_connections<N: FlowNodeSet>(
 fn draw_partial_connections<N: FlowNodeSet>(
     mut commands: Commands,
     cursor: Res<CursorPosition>,
-    mut q_connections: Query<(Entity, &PartialConnection, &mut Path)>,
-    q_start: Query<&GlobalTransform, Or<(With<FlowNodeInput<N>>, With<FlowNodeOutput>)>>,
+    theme: Res<FlowNodeTheme>,
+    mut q_connections: Query<(Entity, &PartialConnection<N>, &mut Path, &mut Stroke)>,
+    q_start: Query<(
+        &GlobalTransform,
+        Option<&FlowNodeInput<N>>,
+        Option<&FlowNodeOutput>,
+    )>,
 ) {
-    for (entity, connection, mut path) in q_connections.iter_mut() {
+    for (entity, connection, mut path, mut stroke) in q_connections.iter_mut() {
         let connection_entity = if connection.input.is_some() {
             connection.input
         } else {
@@ -301,23 +581,20 @@ fn draw_partial_connections<N: FlowNodeSet>(
         };
 
         if let Some(connection_entity) = connection_entity {
-            if let Ok(transform) = q_start.get(connection_entity) {
-                let mut path_builder = PathBuilder::new();
+            if let Ok((transform, input, output)) = q_start.get(connection_entity) {
+                let style = match (input, output) {
+                    (Some(input), _) => input.style,
+                    (_, Some(output)) => output.style,
+                    _ => {
+                        commands.entity(entity).despawn_recursive();
+                        continue;
+                    }
+                };
                 let start = transform.translation().truncate();
-                let end = cursor.position();
-                let half_x = (end.x - start.x) / 2.0;
-                let ctrl_1 = Vec2::new(start.x + half_x, start.y);
-                let ctrl_2 = Vec2::new(start.x + half_x, end.y);
-
-                path_builder.move_to(Vec2::new(
-                    transform.translation().x,
-                    transform.translation().y,
-                ));
-                path_builder.cubic_bezier_to(ctrl_1, ctrl_2, end);
-
-                let line = path_builder.build();
+                let samples = CONNECTION_SEGMENTS * SEGMENT_SAMPLES;
 
-                *path = ShapePath::build_as(&line);
+                *path = wire_path(start, cursor.position(), 0.0, 1.0, samples);
+                stroke.color = port_color(&style, &theme);
             } else {
                 commands.entity(entity).despawn_recursive();
             }
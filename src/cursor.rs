@@ -1,5 +1,10 @@
-use bevy::prelude::*;
+use bevy::{prelude::*, render::camera::NormalizedRenderTarget, window::PrimaryWindow};
 
+use crate::editor::{EditorPanel, EditorSet};
+
+/// Marks the camera of a node editor, see [`EditorPlugin`](crate::editor::EditorPlugin).
+///
+/// The editor lives in the window its camera renders to.
 #[derive(Component)]
 pub struct CursorCamera;
 
@@ -8,12 +13,21 @@ pub struct CursorPlugin;
 impl Plugin for CursorPlugin {
     fn build(&self, app: &mut App) {
         app.insert_resource(CursorPosition::default())
-            .add_systems(Update, update_cursor_position);
+            .add_systems(Update, update_cursor_position.in_set(EditorSet::Input));
     }
 }
 
+/// The cursor in the editor under it.
+///
+/// The editor is kept while a mouse button is held, so drags continue when the cursor leaves
+/// its viewport.
 #[derive(Default, Resource)]
 pub struct CursorPosition {
+    pub editor: Option<Entity>,
+    /// The area of `editor` on screen, in logical pixels.
+    pub editor_rect: Rect,
+    /// The window the cursor is in, `screen_x` and `screen_y` are relative to it.
+    pub window: Option<Entity>,
     pub x: f32,
     pub y: f32,
     pub screen_x: f32,
@@ -28,21 +42,227 @@ impl CursorPosition {
 
 fn update_cursor_position(
     mut cursor: ResMut<CursorPosition>,
-    q_windows: Query<&Window>,
-    camera: Query<(&Camera, &GlobalTransform), With<CursorCamera>>,
+    mouse: Res<Input<MouseButton>>,
+    ui_scale: Res<UiScale>,
+    q_primary: Query<Entity, With<PrimaryWindow>>,
+    q_windows: Query<(Entity, &Window)>,
+    q_camera: Query<(Entity, &Camera, &GlobalTransform), With<CursorCamera>>,
+    q_panels: Query<(&EditorPanel, &Node, &GlobalTransform)>,
 ) {
-    let (camera, camera_transform) = camera.single();
-    let window = q_windows.single();
-
-    if let Some(screen_position) = window.cursor_position() {
-        if let Some(world_position) = camera
-            .viewport_to_world(camera_transform, screen_position)
-            .map(|ray| ray.origin.truncate())
+    let primary = q_primary.get_single().ok();
+    let captured = cursor
+        .editor
+        .filter(|editor| mouse.get_pressed().next().is_some() && q_camera.contains(*editor));
+    // A captured editor keeps following the cursor in its own window only.
+    let Some((window_entity, screen_position)) = q_windows
+        .iter()
+        .filter(|(entity, _)| captured.is_none() || cursor.window == Some(*entity))
+        .find_map(|(entity, window)| window.cursor_position().map(|position| (entity, position)))
+    else {
+        return;
+    };
+    // UI nodes are only laid out in the primary window.
+    let editor_rect = |entity: Entity, camera: &Camera| {
+        if let Some((_, node, transform)) = q_panels
+            .iter()
+            .find(|(panel, _, _)| panel.0 == entity && primary == Some(window_entity))
         {
-            cursor.x = world_position.x;
-            cursor.y = world_position.y;
-            cursor.screen_x = screen_position.x;
-            cursor.screen_y = screen_position.y;
+            let rect = node.logical_rect(transform);
+            let scale = ui_scale.0 as f32;
+
+            return Some(Rect::from_corners(rect.min * scale, rect.max * scale));
         }
+
+        match camera.target.normalize(primary) {
+            Some(NormalizedRenderTarget::Window(window_ref))
+                if window_ref.entity() == window_entity =>
+            {
+                camera.logical_viewport_rect()
+            }
+            _ => None,
+        }
+    };
+    let editor = captured.or_else(|| {
+        q_camera
+            .iter()
+            .filter(|(entity, camera, _)| {
+                camera.is_active
+                    && editor_rect(*entity, camera)
+                        .is_some_and(|rect| rect.contains(screen_position))
+            })
+            .max_by_key(|(_, camera, _)| camera.order)
+            .map(|(entity, _, _)| entity)
+    });
+
+    cursor.editor = editor;
+    cursor.window = Some(window_entity);
+    cursor.screen_x = screen_position.x;
+    cursor.screen_y = screen_position.y;
+
+    let Some((entity, camera, camera_transform)) = editor.and_then(|e| q_camera.get(e).ok()) else {
+        return;
+    };
+    let Some(rect) = editor_rect(entity, camera) else {
+        return;
+    };
+
+    cursor.editor_rect = rect;
+
+    if let Some(world_position) = screen_to_world(camera, camera_transform, rect, screen_position) {
+        cursor.x = world_position.x;
+        cursor.y = world_position.y;
+    }
+}
+
+/// Maps a position on screen onto the canvas of the editor shown in `rect`.
+pub(crate) fn screen_to_world(
+    camera: &Camera,
+    camera_transform: &GlobalTransform,
+    rect: Rect,
+    screen_position: Vec2,
+) -> Option<Vec2> {
+    if rect.is_empty() {
+        return None;
+    }
+
+    // Panels showing an image can differ in size from the image itself.
+    let viewport_size = camera.logical_viewport_size().unwrap_or(rect.size());
+    let viewport_position = (screen_position - rect.min) / rect.size() * viewport_size;
+
+    camera
+        .viewport_to_world(camera_transform, viewport_position)
+        .map(|ray| ray.origin.truncate())
+}
+
+/// The canvas distance covered by one logical pixel, for every scaling mode.
+pub(crate) fn world_per_pixel(camera: &Camera, projection: &OrthographicProjection) -> Vec2 {
+    camera
+        .logical_viewport_size()
+        .map_or(Vec2::splat(projection.scale), |size| {
+            projection.area.size() / size
+        })
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use bevy::{
+        render::camera::{camera_system, ManualTextureViews, ScalingMode, Viewport},
+        window::{WindowCreated, WindowResized, WindowResolution},
+    };
+
+    use super::*;
+
+    /// A headless app with an 800x600 primary window at a scale factor of 2, and an editor
+    /// showing 100 world units vertically in a 300x200 viewport at (100, 100), logical pixels.
+    pub(crate) fn editor_app() -> (App, Entity) {
+        let mut app = App::new();
+
+        app.add_plugins((MinimalPlugins, TransformPlugin))
+            .init_resource::<Assets<Image>>()
+            .init_resource::<Input<KeyCode>>()
+            .init_resource::<Input<MouseButton>>()
+            .init_resource::<ManualTextureViews>()
+            .init_resource::<UiScale>()
+            .add_event::<AssetEvent<Image>>()
+            .add_event::<WindowCreated>()
+            .add_event::<WindowResized>()
+            .add_systems(PostUpdate, camera_system::<OrthographicProjection>);
+
+        app.world.spawn((
+            Window {
+                resolution: WindowResolution::new(800.0, 600.0).with_scale_factor_override(2.0),
+                ..default()
+            },
+            PrimaryWindow,
+        ));
+
+        let mut camera = Camera2dBundle::default();
+
+        camera.camera.viewport = Some(Viewport {
+            physical_position: UVec2::new(200, 200),
+            physical_size: UVec2::new(600, 400),
+            ..default()
+        });
+        camera.projection.scaling_mode = ScalingMode::FixedVertical(100.0);
+
+        let editor = app.world.spawn((camera, CursorCamera)).id();
+
+        app.update();
+
+        (app, editor)
+    }
+
+    pub(crate) fn assert_near(a: Vec2, b: Vec2) {
+        assert!(a.distance(b) < 1e-3, "{a} is not {b}");
+    }
+
+    fn editor_camera(
+        app: &mut App,
+        editor: Entity,
+    ) -> (&Camera, &GlobalTransform, &OrthographicProjection) {
+        app.world
+            .query::<(&Camera, &GlobalTransform, &OrthographicProjection)>()
+            .get(&app.world, editor)
+            .unwrap()
+    }
+
+    #[test]
+    fn world_per_pixel_follows_scaling_mode_and_scale() {
+        let (mut app, editor) = editor_app();
+        let (camera, _, projection) = editor_camera(&mut app, editor);
+
+        assert_near(world_per_pixel(camera, projection), Vec2::splat(0.5));
+
+        app.world
+            .get_mut::<OrthographicProjection>(editor)
+            .unwrap()
+            .scale = 2.0;
+        app.update();
+
+        let (camera, _, projection) = editor_camera(&mut app, editor);
+
+        assert_near(world_per_pixel(camera, projection), Vec2::ONE);
+    }
+
+    #[test]
+    fn screen_to_world_maps_through_viewport() {
+        let (mut app, editor) = editor_app();
+        let (camera, transform, _) = editor_camera(&mut app, editor);
+        let rect = camera.logical_viewport_rect().unwrap();
+        let to_world = |position| screen_to_world(camera, transform, rect, position).unwrap();
+
+        assert_near(rect.min, Vec2::new(100.0, 100.0));
+        assert_near(rect.max, Vec2::new(400.0, 300.0));
+        assert_near(to_world(Vec2::new(250.0, 200.0)), Vec2::ZERO);
+        assert_near(to_world(Vec2::new(100.0, 100.0)), Vec2::new(-75.0, 50.0));
+        assert_near(to_world(Vec2::new(400.0, 300.0)), Vec2::new(75.0, -50.0));
+    }
+
+    #[test]
+    fn cursor_follows_window_into_editor() {
+        let (mut app, editor) = editor_app();
+
+        app.init_resource::<CursorPosition>()
+            .add_systems(Update, update_cursor_position);
+
+        let mut q_windows = app.world.query::<&mut Window>();
+
+        q_windows
+            .single_mut(&mut app.world)
+            .set_cursor_position(Some(Vec2::new(310.0, 240.0)));
+        app.update();
+
+        let cursor = app.world.resource::<CursorPosition>();
+
+        assert_eq!(cursor.editor, Some(editor));
+        assert_near(cursor.position(), Vec2::new(30.0, -20.0));
+
+        // Outside of the viewport the cursor leaves the editor.
+        q_windows
+            .single_mut(&mut app.world)
+            .set_cursor_position(Some(Vec2::new(50.0, 50.0)));
+        app.update();
+
+        assert_eq!(app.world.resource::<CursorPosition>().editor, None);
     }
 }
@@ -0,0 +1,199 @@
+use bevy::{
+    core_pipeline::clear_color::ClearColorConfig,
+    prelude::*,
+    render::{
+        camera::{NormalizedRenderTarget, Viewport},
+        view::RenderLayers,
+    },
+    transform::TransformSystem,
+    window::PrimaryWindow,
+};
+
+use crate::{
+    assets::DefaultAssetsPlugin,
+    bindings::FlowNodeBindings,
+    cursor::{CursorCamera, CursorPlugin},
+    focus::EditorFocus,
+    interactions::InteractionPlugin,
+    node::ActiveNode,
+    notice::NoticePlugin,
+    theme::FlowNodeTheme,
+};
+
+/// Everything shared by the editors of every [`FlowNodeSet`](crate::node_set::FlowNodeSet).
+///
+/// Each [`CursorCamera`] is an editor: it owns the nodes spawned into it, their selection and
+/// the cursor used to interact with them. Nodes are only rendered by their editor's camera when
+/// that camera has [`RenderLayers`].
+pub struct EditorPlugin;
+
+impl Plugin for EditorPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins((
+            DefaultAssetsPlugin,
+            CursorPlugin,
+            InteractionPlugin,
+            NoticePlugin,
+        ))
+        .init_resource::<EditorFocus>()
+        .init_resource::<EditorMode>()
+        .init_resource::<FlowNodeBindings>()
+        .init_resource::<FlowNodeTheme>()
+        .configure_sets(
+            Update,
+            (
+                EditorSet::Input.run_if(editor_enabled),
+                EditorSet::Interaction.run_if(editor_enabled),
+                EditorSet::Layout,
+                EditorSet::Evaluate,
+                EditorSet::Render,
+            )
+                .chain(),
+        )
+        .add_systems(PreUpdate, init_editors)
+        .add_systems(Update, apply_editor_background.in_set(EditorSet::Render))
+        .add_systems(
+            PostUpdate,
+            (
+                fit_panel_viewports.after(TransformSystem::TransformPropagate),
+                propagate_render_layers,
+            ),
+        );
+    }
+}
+
+/// The stages the editor runs in every `Update`, in order.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, SystemSet)]
+pub enum EditorSet {
+    /// Tracks the cursor and turns mouse presses into clicks.
+    Input,
+    /// Reacts to the user: selecting, dragging, wiring, menus, widgets and the camera.
+    Interaction,
+    /// Builds nodes and wires and moves nodes around.
+    Layout,
+    /// Resolves and executes the graphs.
+    Evaluate,
+    /// Brings the drawn wires, grids, highlights and overlays up to date.
+    Render,
+}
+
+/// Turns the editors on and off at runtime, for instance while a modal dialog is open.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Resource)]
+pub enum EditorMode {
+    #[default]
+    Editable,
+    /// Graphs can be looked at but not changed by the user.
+    ReadOnly,
+    /// The editors ignore the user, graphs are still built, evaluated and drawn.
+    Disabled,
+}
+
+/// Runs a system unless the editors are [`EditorMode::Disabled`].
+pub fn editor_enabled(mode: Res<EditorMode>) -> bool {
+    *mode != EditorMode::Disabled
+}
+
+/// Runs a system only when the editors are [`EditorMode::Editable`].
+pub fn editor_editable(mode: Res<EditorMode>) -> bool {
+    *mode == EditorMode::Editable
+}
+
+/// The editor an entity belongs to.
+#[derive(Clone, Copy, Component, Debug, PartialEq, Eq)]
+pub struct InEditor(pub Entity);
+
+/// Hosts an editor inside a UI node.
+///
+/// An editor camera rendering to a window is fitted to the node. One rendering to an image is
+/// left as is, the image is expected to be shown by the node and the cursor is mapped onto it.
+#[derive(Clone, Copy, Component, Debug, PartialEq, Eq)]
+pub struct EditorPanel(pub Entity);
+
+fn fit_panel_viewports(
+    ui_scale: Res<UiScale>,
+    q_panels: Query<(&EditorPanel, &Node, &GlobalTransform)>,
+    q_primary: Query<Entity, With<PrimaryWindow>>,
+    q_windows: Query<&Window>,
+    mut q_editors: Query<&mut Camera, With<CursorCamera>>,
+) {
+    for (panel, node, transform) in q_panels.iter() {
+        let Ok(mut camera) = q_editors.get_mut(panel.0) else {
+            continue;
+        };
+        let Some(NormalizedRenderTarget::Window(window_ref)) =
+            camera.target.normalize(q_primary.get_single().ok())
+        else {
+            continue;
+        };
+        let Ok(window) = q_windows.get(window_ref.entity()) else {
+            continue;
+        };
+
+        let window_size = UVec2::new(window.physical_width(), window.physical_height());
+        let rect = node.physical_rect(transform, window.scale_factor(), ui_scale.0);
+        let position = rect.min.max(Vec2::ZERO).as_uvec2().min(window_size);
+        let size = rect.size().as_uvec2().min(window_size - position);
+
+        if size.cmpeq(UVec2::ZERO).any() {
+            continue;
+        }
+
+        let fitted = camera.viewport.as_ref().is_some_and(|viewport| {
+            viewport.physical_position == position && viewport.physical_size == size
+        });
+
+        if !fitted {
+            camera.viewport = Some(Viewport {
+                physical_position: position,
+                physical_size: size,
+                ..default()
+            });
+        }
+    }
+}
+
+fn apply_editor_background(
+    theme: Res<FlowNodeTheme>,
+    mut q_editors: Query<(Ref<CursorCamera>, &mut Camera2d)>,
+) {
+    let Some(background) = theme.background else {
+        return;
+    };
+
+    for (editor, mut camera) in q_editors.iter_mut() {
+        if theme.is_changed() || editor.is_added() {
+            camera.clear_color = ClearColorConfig::Custom(background);
+        }
+    }
+}
+
+fn init_editors(
+    mut commands: Commands,
+    q_editors: Query<Entity, (With<CursorCamera>, Without<ActiveNode>)>,
+) {
+    for entity in q_editors.iter() {
+        commands.entity(entity).insert(ActiveNode::default());
+    }
+}
+
+fn propagate_render_layers(
+    mut commands: Commands,
+    q_editors: Query<&RenderLayers, With<CursorCamera>>,
+    q_roots: Query<(Entity, &InEditor), Without<Parent>>,
+    q_children: Query<&Children>,
+    q_layers: Query<Option<&RenderLayers>>,
+) {
+    for (root, editor) in q_roots.iter() {
+        let Ok(layers) = q_editors.get(editor.0) else {
+            continue;
+        };
+
+        for entity in std::iter::once(root).chain(q_children.iter_descendants(root)) {
+            if let Ok(current) = q_layers.get(entity) {
+                if current != Some(layers) {
+                    commands.entity(entity).insert(*layers);
+                }
+            }
+        }
+    }
+}
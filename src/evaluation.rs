@@ -0,0 +1,256 @@
+use bevy::{ecs::system::SystemParamItem, prelude::*};
+use std::{fmt, time::Duration};
+
+use crate::node_set::FlowNodeSet;
+
+/// When the output nodes of every graph are resolved, [`EvaluateNodes`] resolves them in any
+/// mode.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Resource)]
+pub enum EvaluationMode {
+    Manual,
+    /// Whenever a wire, an input widget or a runtime input changes.
+    #[default]
+    OnChange,
+    EveryFrame,
+    /// Every given step of game time, catching up on as many steps as a frame took up to eight,
+    /// the time left over after a longer stall is dropped. Nodes read a clock advancing by
+    /// exactly one step, a zero step never resolves.
+    FixedTimestep(Duration),
+}
+
+// The most steps a fixed timestep catches up on in a single frame.
+const MAX_FIXED_STEPS: usize = 8;
+
+/// Resolves the output nodes of every graph this frame.
+#[derive(Clone, Copy, Debug, Default, Event)]
+pub struct EvaluateNodes;
+
+/// What a node can read while it is resolved or executed.
+pub struct EvalContext<'a, 'w, 's, N: FlowNodeSet> {
+    /// The node being evaluated, or the entity holding the
+    /// [`RuntimeGraph`](crate::runtime::RuntimeGraph) it belongs to.
+    pub entity: Entity,
+    /// The [`FlowNodeSet::Context`] of the node set, to read the world through.
+    pub params: &'a SystemParamItem<'w, 's, N::Context>,
+    pub time: &'a Time,
+    pub title: &'a str,
+}
+
+// Keeps the game time of graphs evaluated at a fixed rate.
+#[derive(Default)]
+pub(crate) struct EvaluationClock {
+    clock: Time,
+    lag: Duration,
+}
+
+impl EvaluationClock {
+    /// The clocks to resolve graphs with this frame in the time-driven modes.
+    pub(crate) fn tick(&mut self, mode: EvaluationMode, time: &Time) -> Vec<Time> {
+        match mode {
+            EvaluationMode::EveryFrame => vec![*time],
+            EvaluationMode::FixedTimestep(step) => {
+                let mut steps = Vec::new();
+
+                if step.is_zero() {
+                    return steps;
+                }
+
+                self.lag += time.delta();
+
+                while self.lag >= step {
+                    if steps.len() == MAX_FIXED_STEPS {
+                        self.lag = Duration::ZERO;
+                        break;
+                    }
+
+                    self.lag -= step;
+                    self.clock.advance_by(step);
+                    steps.push(self.clock);
+                }
+
+                steps
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// The clock to resolve a graph with outside of [`EvaluationClock::tick`].
+    pub(crate) fn now(&self, mode: EvaluationMode, time: &Time) -> Time {
+        match mode {
+            EvaluationMode::FixedTimestep(_) => self.clock,
+            _ => *time,
+        }
+    }
+}
+
+/// Where [`NodeInputs`] read the data inputs of a node from.
+pub trait InputSource<N: FlowNodeSet> {
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    fn label(&self, index: usize) -> Option<&str>;
+    /// The number of data inputs.
+    fn len(&self) -> usize;
+    /// The value plugged into an input, `None` when nothing is connected to it.
+    fn value(&self, index: usize) -> Option<N::NodeIO>;
+}
+
+impl<N: FlowNodeSet> InputSource<N> for Vec<(String, Option<N::NodeIO>)> {
+    fn label(&self, index: usize) -> Option<&str> {
+        self.get(index).map(|(label, _)| label.as_str())
+    }
+
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    fn value(&self, index: usize) -> Option<N::NodeIO> {
+        self.get(index).and_then(|(_, value)| value.clone())
+    }
+}
+
+/// The data inputs of a node in the order of its template, values are resolved from upstream
+/// nodes as they are read.
+pub struct NodeInputs<'a, N: FlowNodeSet>(&'a dyn InputSource<N>);
+
+impl<'a, N: FlowNodeSet> Clone for NodeInputs<'a, N> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, N: FlowNodeSet> Copy for NodeInputs<'a, N> {}
+
+impl<'a, N: FlowNodeSet> NodeInputs<'a, N> {
+    pub fn new(source: &'a dyn InputSource<N>) -> Self {
+        Self(source)
+    }
+
+    /// The value of the input labelled `label`, `None` when there is no such input or nothing
+    /// is connected to it.
+    pub fn get(&self, label: &str) -> Option<N::NodeIO> {
+        self.get_index(self.index_of(label)?)
+    }
+
+    pub fn get_index(&self, index: usize) -> Option<N::NodeIO> {
+        self.0.value(index)
+    }
+
+    pub fn get_or(&self, label: &str, default: N::NodeIO) -> N::NodeIO {
+        self.get(label).unwrap_or(default)
+    }
+
+    pub fn index_of(&self, label: &str) -> Option<usize> {
+        (0..self.len()).find(|&index| self.label(index) == Some(label))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Every input with its value, resolving each of them.
+    pub fn iter(&self) -> impl Iterator<Item = (&'a str, Option<N::NodeIO>)> + 'a {
+        let source = self.0;
+
+        (0..source.len()).filter_map(move |index| Some((source.label(index)?, source.value(index))))
+    }
+
+    pub fn label(&self, index: usize) -> Option<&'a str> {
+        self.0.label(index)
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// The value of the input labelled `label`, for nodes that cannot do without it.
+    pub fn require(&self, label: &str) -> Result<N::NodeIO, MissingInput> {
+        let index = self
+            .index_of(label)
+            .ok_or_else(|| MissingInput::Unknown(label.to_string()))?;
+
+        self.get_index(index)
+            .ok_or_else(|| MissingInput::NotConnected(label.to_string()))
+    }
+}
+
+/// An input read through [`NodeInputs::require`] has no value.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MissingInput {
+    /// Nothing is connected to the input.
+    NotConnected(String),
+    /// The node has no input with the label, usually a typo.
+    Unknown(String),
+}
+
+impl fmt::Display for MissingInput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MissingInput::NotConnected(label) => write!(f, "input \"{label}\" is not connected"),
+            MissingInput::Unknown(label) => write!(f, "there is no input labelled \"{label}\""),
+        }
+    }
+}
+
+impl std::error::Error for MissingInput {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::tests::TestNodes;
+
+    fn frame(delta: Duration) -> Time {
+        let mut time = Time::default();
+
+        time.advance_by(delta);
+        time
+    }
+
+    #[test]
+    fn fixed_timestep_catches_up() {
+        let mut clock = EvaluationClock::default();
+        let mode = EvaluationMode::FixedTimestep(Duration::from_millis(10));
+
+        assert!(clock
+            .tick(mode, &frame(Duration::from_millis(5)))
+            .is_empty());
+
+        let steps = clock.tick(mode, &frame(Duration::from_millis(25)));
+
+        assert_eq!(steps.len(), 3);
+        assert_eq!(steps[2].elapsed(), Duration::from_millis(30));
+    }
+
+    #[test]
+    fn fixed_timestep_drops_stalls() {
+        let mut clock = EvaluationClock::default();
+        let mode = EvaluationMode::FixedTimestep(Duration::from_millis(10));
+
+        let steps = clock.tick(mode, &frame(Duration::from_secs(60)));
+
+        assert_eq!(steps.len(), MAX_FIXED_STEPS);
+        assert_eq!(clock.tick(mode, &frame(Duration::from_millis(10))).len(), 1);
+        assert!(clock
+            .tick(
+                EvaluationMode::FixedTimestep(Duration::ZERO),
+                &frame(Duration::from_secs(1))
+            )
+            .is_empty());
+    }
+
+    #[test]
+    fn require_tells_typos_from_unwired_inputs() {
+        let source = vec![("a".to_string(), Some(1)), ("b".to_string(), None)];
+        let inputs = NodeInputs::<TestNodes>::new(&source);
+
+        assert_eq!(inputs.require("a"), Ok(1));
+        assert_eq!(
+            inputs.require("b"),
+            Err(MissingInput::NotConnected("b".to_string()))
+        );
+        assert_eq!(
+            inputs.require("c"),
+            Err(MissingInput::Unknown("c".to_string()))
+        );
+    }
+}
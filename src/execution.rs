@@ -0,0 +1,118 @@
+use bevy::prelude::*;
+use std::marker::PhantomData;
+
+use crate::{
+    editor::EditorSet,
+    graph::{reset_node_states, FlowNodeGraph},
+    node::{FlowNode, FlowNodeEvent, FlowNodeInput, FlowNodeOutput, PortKind},
+    node_set::{ExecFlow, FlowNodeSet},
+};
+
+// Guards against execution cycles in user graphs.
+const MAX_EXEC_STEPS: usize = 1024;
+
+pub struct ExecutionPlugin<N: FlowNodeSet>(PhantomData<N>);
+
+impl<N: FlowNodeSet> Default for ExecutionPlugin<N> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<N: FlowNodeSet> Plugin for ExecutionPlugin<N> {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ExecTrigger>().add_systems(
+            Update,
+            execute_flow::<N>
+                .after(reset_node_states::<N>)
+                .in_set(EditorSet::Evaluate),
+        );
+    }
+}
+
+/// Marks an entry point of execution, set through [`FlowNodeTemplate::event`].
+///
+/// [`FlowNodeTemplate::event`]: crate::template::FlowNodeTemplate::event
+#[derive(Clone, Component)]
+pub struct EventNode(pub String);
+
+/// Starts execution at every [`EventNode`] with a matching name.
+#[derive(Clone, Event)]
+pub struct ExecTrigger(pub String);
+
+fn execute_flow<N: FlowNodeSet>(
+    graph: FlowNodeGraph<N>,
+    mut ev_trigger: EventReader<ExecTrigger>,
+    mut ev_node: EventWriter<FlowNodeEvent<N>>,
+    q_events: Query<(Entity, &EventNode)>,
+    q_exec: Query<&Children, With<FlowNode<N>>>,
+    q_inputs: Query<(&Parent, &FlowNodeInput<N>)>,
+    q_outputs: Query<(&Parent, &FlowNodeOutput)>,
+    q_transforms: Query<&GlobalTransform>,
+) {
+    for ExecTrigger(name) in ev_trigger.read() {
+        let mut stack = q_events
+            .iter()
+            .filter(|(_, event)| event.0 == *name)
+            .map(|(entity, _)| entity)
+            .collect::<Vec<_>>();
+        let mut steps = 0;
+
+        while let Some(entity) = stack.pop() {
+            steps += 1;
+
+            if steps > MAX_EXEC_STEPS {
+                warn!("Execution of \"{name}\" stopped after {MAX_EXEC_STEPS} steps");
+                break;
+            }
+
+            let (Ok(children), Some((flow, inputs))) = (q_exec.get(entity), graph.execute(entity))
+            else {
+                continue;
+            };
+
+            ev_node.send(FlowNodeEvent::Executed((entity, inputs)));
+
+            let mut next = Vec::new();
+
+            for &child in children.iter() {
+                let Ok((_, output)) = q_outputs.get(child) else {
+                    continue;
+                };
+
+                if output.kind != PortKind::Exec {
+                    continue;
+                }
+
+                let follow = match &flow {
+                    ExecFlow::Continue => true,
+                    ExecFlow::Output(label) => output.label == *label,
+                    ExecFlow::Stop => false,
+                };
+
+                if !follow {
+                    continue;
+                }
+
+                let mut targets = q_inputs
+                    .iter()
+                    .filter(|(_, input)| input.connection == Some(child))
+                    .map(|(parent, _)| parent.get())
+                    .collect::<Vec<_>>();
+
+                targets.sort_by(|a, b| {
+                    let y = |entity| {
+                        q_transforms
+                            .get(entity)
+                            .map_or(0.0, |transform: &GlobalTransform| transform.translation().y)
+                    };
+
+                    y(*b).total_cmp(&y(*a))
+                });
+                next.extend(targets);
+            }
+
+            stack.extend(next.into_iter().rev());
+        }
+    }
+}
@@ -0,0 +1,13 @@
+use bevy::prelude::*;
+
+/// The editor element that currently receives keyboard input.
+///
+/// Keyboard bindings only fire for the focused element, so typing into a widget never
+/// triggers canvas shortcuts such as opening the menu or deleting a node.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Resource)]
+pub enum EditorFocus {
+    #[default]
+    Canvas,
+    Menu,
+    Widget(Entity),
+}
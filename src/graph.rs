@@ -0,0 +1,500 @@
+#![allow(clippy::type_complexity)]
+
+use bevy::{
+    ecs::system::{StaticSystemParam, SystemParam},
+    prelude::*,
+};
+use std::{
+    cell::{OnceCell, RefCell},
+    collections::{HashMap, HashSet},
+    fmt,
+    sync::{Mutex, PoisonError},
+};
+
+use crate::{
+    connection::ConnectionEvent,
+    editor::InEditor,
+    evaluation::{
+        EvalContext,
+        EvaluateNodes,
+        EvaluationClock,
+        EvaluationMode,
+        InputSource,
+        NodeInputs,
+    },
+    node::{
+        FlowNode,
+        FlowNodeEvent,
+        FlowNodeInput,
+        FlowNodeOutput,
+        FlowNodeTitle,
+        OutputNode,
+        PortKind,
+    },
+    node_set::{ExecFlow, FlowNodeSet},
+    runtime::{SavedConnection, SavedGraph, SavedNode},
+};
+
+/// Resolves the values flowing through the graphs of `N`.
+#[derive(SystemParam)]
+pub struct FlowNodeGraph<'w, 's, N: FlowNodeSet> {
+    nodes: Query<
+        'w,
+        's,
+        (
+            &'static FlowNode<N>,
+            &'static FlowNodeState<N>,
+            Option<&'static FlowNodeTitle>,
+            Option<&'static Children>,
+        ),
+    >,
+    editors: Query<'w, 's, (Entity, &'static InEditor, Has<OutputNode>), With<FlowNode<N>>>,
+    inputs: Query<'w, 's, &'static FlowNodeInput<N>>,
+    outputs: Query<'w, 's, (&'static Parent, &'static FlowNodeOutput)>,
+    params: StaticSystemParam<'w, 's, <N as FlowNodeSet>::Context>,
+    time: Res<'w, Time>,
+}
+
+impl<'w, 's, N: FlowNodeSet> FlowNodeGraph<'w, 's, N> {
+    /// Resolves the output labelled `output` of the node `entity`, output nodes are resolved
+    /// with `None`. Every call is a pass of its own, advancing stateful nodes again.
+    pub fn resolve(&self, entity: Entity, output: Option<&str>) -> Option<N::NodeIO> {
+        Evaluation::new(self, &self.time).resolve(entity, output)
+    }
+
+    /// Resolves the output nodes `entities` in a single pass, resolving every node upstream of
+    /// them once.
+    pub(crate) fn resolve_all_at(
+        &self,
+        entities: impl IntoIterator<Item = Entity>,
+        time: &Time,
+    ) -> Vec<(Entity, Option<N::NodeIO>)> {
+        let evaluation = Evaluation::new(self, time);
+
+        entities
+            .into_iter()
+            .map(|entity| (entity, evaluation.resolve(entity, None)))
+            .collect()
+    }
+
+    /// Runs the node `entity`, returning where execution continues and the data inputs it read.
+    pub(crate) fn execute(
+        &self,
+        entity: Entity,
+    ) -> Option<(ExecFlow, Vec<(String, Option<N::NodeIO>)>)> {
+        let (node, state, title, _) = self.nodes.get(entity).ok()?;
+        let inputs = self.input_values(entity);
+        let evaluation = Evaluation::new(self, &self.time);
+        let context = evaluation.context(entity, title);
+        let flow = state.with(|state| node.execute(&context, state, &NodeInputs::new(&inputs)));
+
+        Some((flow, inputs))
+    }
+
+    /// Saves the nodes of `editor` and the data wires between them, to resolve them with a
+    /// [`RuntimeGraph`](crate::runtime::RuntimeGraph).
+    ///
+    /// Runtime graphs are only resolved, never executed, so graphs with execution wires are
+    /// refused rather than saved without them. Unwired execution ports are left out.
+    pub fn save(&self, editor: Entity) -> Result<SavedGraph<N>, SaveError> {
+        let entities = self
+            .editors
+            .iter()
+            .filter(|(entity, in_editor, _)| in_editor.0 == editor && self.nodes.contains(*entity))
+            .map(|(entity, _, output)| (entity, output))
+            .collect::<Vec<_>>();
+        let indices = entities
+            .iter()
+            .enumerate()
+            .map(|(index, (entity, _))| (*entity, index))
+            .collect::<HashMap<_, _>>();
+        let mut graph = SavedGraph {
+            connections: Vec::new(),
+            nodes: Vec::new(),
+        };
+
+        for (to, (entity, output)) in entities.into_iter().enumerate() {
+            let Ok((node, state, title, children)) = self.nodes.get(entity) else {
+                continue;
+            };
+            let ports = children.map_or(&[][..], |children| children);
+            let title = title.map_or_else(String::new, |title| title.0.clone());
+            let exec_wire = ports
+                .iter()
+                .filter_map(|port| self.inputs.get(*port).ok())
+                .find(|input| input.kind == PortKind::Exec && input.connection.is_some());
+
+            if let Some(input) = exec_wire {
+                return Err(SaveError::ExecWire {
+                    input: input.label.clone(),
+                    node: title,
+                });
+            }
+
+            let inputs = ports
+                .iter()
+                .filter_map(|port| self.inputs.get(*port).ok())
+                .filter(|input| input.kind == PortKind::Data);
+
+            for input in inputs.clone() {
+                let Some((parent, from_output)) = input
+                    .connection
+                    .and_then(|output| self.outputs.get(output).ok())
+                else {
+                    continue;
+                };
+
+                if let Some(&from) = indices.get(&parent.get()) {
+                    graph.connections.push(SavedConnection {
+                        from,
+                        input: input.label.clone(),
+                        output: from_output.label.clone(),
+                        to,
+                    });
+                }
+            }
+
+            graph.nodes.push(SavedNode {
+                inputs: inputs.map(|input| input.label.clone()).collect(),
+                node: node.0.clone(),
+                output,
+                outputs: ports
+                    .iter()
+                    .filter_map(|port| self.outputs.get(*port).ok())
+                    .filter(|(_, output)| output.kind == PortKind::Data)
+                    .map(|(_, output)| output.label.clone())
+                    .collect(),
+                state: state.get(),
+                title,
+            });
+        }
+
+        Ok(graph)
+    }
+
+    /// Resolves every data input of the node `entity` once.
+    pub fn input_values(&self, entity: Entity) -> Vec<(String, Option<N::NodeIO>)> {
+        let Ok((.., Some(children))) = self.nodes.get(entity) else {
+            return Vec::new();
+        };
+        let evaluation = Evaluation::new(self, &self.time);
+        let ports = NodePorts::new(&evaluation, children);
+
+        NodeInputs::new(&ports)
+            .iter()
+            .map(|(label, value)| (label.to_string(), value))
+            .collect()
+    }
+}
+
+/// Why [`FlowNodeGraph::save`] could not save a graph.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SaveError {
+    /// The input labelled `input` of the node titled `node` is wired to an execution port.
+    ExecWire { input: String, node: String },
+}
+
+impl fmt::Display for SaveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SaveError::ExecWire { input, node } => write!(
+                f,
+                "execution input \"{input}\" of \"{node}\" is wired, runtime graphs are never \
+                 executed"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SaveError {}
+
+// A single pass over the graph. Every output of a node is resolved at most once, and a node
+// reached again through a cycle of wires reads as disconnected.
+struct Evaluation<'a, 'w, 's, N: FlowNodeSet> {
+    graph: &'a FlowNodeGraph<'w, 's, N>,
+    // The outputs of nodes resolving them all at once.
+    outputs: RefCell<HashMap<Entity, HashMap<String, N::NodeIO>>>,
+    resolving: RefCell<HashSet<Entity>>,
+    // The state of nodes before they first stepped in this pass.
+    stepped: RefCell<HashMap<Entity, N::State>>,
+    time: &'a Time,
+    values: RefCell<HashMap<Entity, Vec<(Option<String>, Option<N::NodeIO>)>>>,
+}
+
+impl<'a, 'w, 's, N: FlowNodeSet> Evaluation<'a, 'w, 's, N> {
+    fn new(graph: &'a FlowNodeGraph<'w, 's, N>, time: &'a Time) -> Self {
+        Self {
+            graph,
+            outputs: RefCell::default(),
+            resolving: RefCell::default(),
+            stepped: RefCell::default(),
+            time,
+            values: RefCell::default(),
+        }
+    }
+
+    fn context(
+        &self,
+        entity: Entity,
+        title: Option<&'a FlowNodeTitle>,
+    ) -> EvalContext<'a, 'w, 's, N> {
+        EvalContext {
+            entity,
+            params: &self.graph.params,
+            time: self.time,
+            title: title.map_or("", |title| &title.0),
+        }
+    }
+
+    fn resolve(&self, entity: Entity, output: Option<&str>) -> Option<N::NodeIO> {
+        let resolved = self.values.borrow().get(&entity).and_then(|values| {
+            values
+                .iter()
+                .find(|(label, _)| label.as_deref() == output)
+                .map(|(_, value)| value.clone())
+        });
+
+        if let Some(value) = resolved {
+            return value;
+        }
+
+        if let (Some(output), Some(outputs)) = (output, self.outputs.borrow().get(&entity)) {
+            return outputs.get(output).cloned();
+        }
+
+        if !self.resolving.borrow_mut().insert(entity) {
+            return None;
+        }
+
+        let value = self.resolve_node(entity, output);
+
+        self.resolving.borrow_mut().remove(&entity);
+        self.values
+            .borrow_mut()
+            .entry(entity)
+            .or_default()
+            .push((output.map(str::to_string), value.clone()));
+
+        value
+    }
+
+    fn resolve_node(&self, entity: Entity, output: Option<&str>) -> Option<N::NodeIO> {
+        let (node, state, title, children) = self.graph.nodes.get(entity).ok()?;
+        let ports = NodePorts::new(self, children.map_or(&[], |children| children));
+        let inputs = NodeInputs::new(&ports);
+        let context = self.context(entity, title);
+
+        state.with(|state| {
+            // The other outputs of a node step from the same state as the first one, so the
+            // node only moves on once per pass.
+            let mut before = self.stepped.borrow().get(&entity).cloned();
+
+            if before.is_none() {
+                self.stepped.borrow_mut().insert(entity, state.clone());
+            }
+
+            let state = before.as_mut().unwrap_or(state);
+
+            if let Some(output) = output {
+                if let Some(outputs) = node.resolve_outputs(&context, state, &inputs) {
+                    let value = outputs.get(output).cloned();
+
+                    self.outputs.borrow_mut().insert(entity, outputs);
+
+                    return value;
+                }
+            }
+
+            Some(node.resolve(&context, state, &inputs, output))
+        })
+    }
+}
+
+/// The [`FlowNodeSet::State`] of a node, read it to save the graph and restore it through
+/// [`FlowNodeTemplate::state`](crate::template::FlowNodeTemplate::state).
+#[derive(Component)]
+pub struct FlowNodeState<N: FlowNodeSet>(Mutex<N::State>);
+
+impl<N: FlowNodeSet> FlowNodeState<N> {
+    pub fn new(state: N::State) -> Self {
+        Self(Mutex::new(state))
+    }
+
+    pub fn get(&self) -> N::State {
+        self.with(|state| state.clone())
+    }
+
+    pub fn set(&mut self, state: N::State) {
+        *self.0.get_mut().unwrap_or_else(PoisonError::into_inner) = state;
+    }
+
+    // Nodes are resolved through shared queries and never while they are already being
+    // resolved, the lock is never contended.
+    pub(crate) fn with<R>(&self, f: impl FnOnce(&mut N::State) -> R) -> R {
+        f(&mut self.0.lock().unwrap_or_else(PoisonError::into_inner))
+    }
+}
+
+/// Puts nodes back in their [`FlowNodeSet::initial_state`].
+#[derive(Clone, Copy, Debug, Event, PartialEq, Eq)]
+pub enum ResetNodeState {
+    All,
+    Node(Entity),
+}
+
+pub(crate) fn reset_node_states<N: FlowNodeSet>(
+    mut ev_reset: EventReader<ResetNodeState>,
+    mut q_states: Query<(Entity, &FlowNode<N>, &mut FlowNodeState<N>)>,
+) {
+    for ev in ev_reset.read() {
+        for (entity, node, mut state) in q_states.iter_mut() {
+            if *ev == ResetNodeState::All || *ev == ResetNodeState::Node(entity) {
+                state.set(node.initial_state());
+            }
+        }
+    }
+}
+
+pub(crate) fn resolve_output_nodes<N: FlowNodeSet>(
+    graph: FlowNodeGraph<N>,
+    mode: Res<EvaluationMode>,
+    time: Res<Time>,
+    mut clock: Local<EvaluationClock>,
+    mut ev_connection: EventReader<ConnectionEvent>,
+    mut ev_evaluate: EventReader<EvaluateNodes>,
+    mut ev_resolution: EventWriter<FlowNodeEvent<N>>,
+    q_output: Query<Entity, (With<FlowNode<N>>, With<OutputNode>)>,
+) {
+    let changed = ev_connection.read().count() > 0 && *mode == EvaluationMode::OnChange;
+    let requested = ev_evaluate.read().count() > 0;
+    let mut times = clock.tick(*mode, &time);
+
+    if times.is_empty() && (changed || requested) {
+        times.push(clock.now(*mode, &time));
+    }
+
+    for time in times.iter() {
+        for (entity, value) in graph.resolve_all_at(q_output.iter(), time) {
+            if let Some(value) = value {
+                ev_resolution.send(FlowNodeEvent::Resolved((entity, value)));
+            }
+        }
+    }
+}
+
+// The data inputs among the children of a node.
+struct NodePorts<'a, 'w, 's, N: FlowNodeSet> {
+    evaluation: &'a Evaluation<'a, 'w, 's, N>,
+    // Collected on the first read, nodes reading none of their inputs never walk their ports.
+    inputs: OnceCell<Vec<&'a FlowNodeInput<N>>>,
+    ports: &'a [Entity],
+}
+
+impl<'a, 'w, 's, N: FlowNodeSet> NodePorts<'a, 'w, 's, N> {
+    fn new(evaluation: &'a Evaluation<'a, 'w, 's, N>, ports: &'a [Entity]) -> Self {
+        Self {
+            evaluation,
+            inputs: OnceCell::new(),
+            ports,
+        }
+    }
+
+    fn inputs(&self) -> &[&'a FlowNodeInput<N>] {
+        self.inputs.get_or_init(|| {
+            self.ports
+                .iter()
+                .filter_map(|port| self.evaluation.graph.inputs.get(*port).ok())
+                .filter(|input| input.kind == PortKind::Data)
+                .collect()
+        })
+    }
+}
+
+impl<'a, 'w, 's, N: FlowNodeSet> InputSource<N> for NodePorts<'a, 'w, 's, N> {
+    fn label(&self, index: usize) -> Option<&str> {
+        self.inputs().get(index).map(|input| input.label.as_str())
+    }
+
+    fn len(&self) -> usize {
+        self.inputs().len()
+    }
+
+    fn value(&self, index: usize) -> Option<N::NodeIO> {
+        let connection = self.inputs().get(index)?.connection?;
+        let (parent, output) = self.evaluation.graph.outputs.get(connection).ok()?;
+
+        self.evaluation.resolve(parent.get(), Some(&output.label))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+    use crate::runtime::tests::TestNodes;
+
+    // Two nodes of one editor, with `output` of the first wired into `input` of the second.
+    fn wired(output: FlowNodeOutput, mut input: FlowNodeInput<TestNodes>) -> (World, Entity) {
+        let mut world = World::new();
+
+        world.init_resource::<Time>();
+
+        let editor = world.spawn_empty().id();
+        let node = |world: &mut World| {
+            world
+                .spawn((
+                    FlowNode(TestNodes::Pass),
+                    FlowNodeState::<TestNodes>::new(0),
+                    FlowNodeTitle("Pass".to_string()),
+                    InEditor(editor),
+                ))
+                .id()
+        };
+        let from = node(&mut world);
+        let to = node(&mut world);
+        let output = world.spawn(output).set_parent(from).id();
+
+        input.connection = Some(output);
+        world.spawn(input).set_parent(to);
+
+        (world, editor)
+    }
+
+    fn save(world: &mut World, editor: Entity) -> Result<SavedGraph<TestNodes>, SaveError> {
+        world.run_system_once(move |graph: FlowNodeGraph<TestNodes>| graph.save(editor))
+    }
+
+    #[test]
+    fn save_keeps_data_wires() {
+        let (mut world, editor) = wired(
+            FlowNodeOutput::from_label("value"),
+            FlowNodeInput::from_label("value"),
+        );
+        let graph = save(&mut world, editor).unwrap();
+
+        assert_eq!(graph.nodes.len(), 2);
+        assert_eq!(
+            graph.connections,
+            vec![SavedConnection {
+                from: 0,
+                input: "value".to_string(),
+                output: "value".to_string(),
+                to: 1,
+            }]
+        );
+    }
+
+    #[test]
+    fn save_refuses_exec_wires() {
+        let (mut world, editor) = wired(FlowNodeOutput::exec("then"), FlowNodeInput::exec("run"));
+
+        assert_eq!(
+            save(&mut world, editor).err(),
+            Some(SaveError::ExecWire {
+                input: "run".to_string(),
+                node: "Pass".to_string(),
+            })
+        );
+    }
+}
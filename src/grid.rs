@@ -0,0 +1,219 @@
+use bevy::prelude::*;
+use bevy_prototype_lyon::{entity::ShapeBundle, prelude::*};
+
+use crate::{
+    cursor::{world_per_pixel, CursorCamera},
+    editor::{EditorSet, InEditor},
+    theme::FlowNodeTheme,
+};
+
+// Behind every node, yet in front of the far plane of a default 2D camera.
+const GRID_Z: f32 = -0.05;
+
+/// Draws a background grid in every editor and snaps nodes to it.
+pub struct GridPlugin;
+
+impl Plugin for GridPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<FlowNodeTheme>()
+            .init_resource::<GridConfig>()
+            .add_systems(
+                Update,
+                (
+                    draw_grids.in_set(EditorSet::Render),
+                    spawn_grids.in_set(EditorSet::Layout),
+                ),
+            );
+
+        if !app.is_plugin_added::<ShapePlugin>() {
+            app.add_plugins(ShapePlugin);
+        }
+    }
+}
+
+#[derive(Resource)]
+pub struct GridConfig {
+    /// Draw a major line every this many cells.
+    pub major_every: u32,
+    /// Lines closer than this on screen are hidden, in pixels.
+    pub min_spacing: f32,
+    pub size: f32,
+    /// Snap the top left corner of nodes to the grid when they are spawned or dragged.
+    pub snap: bool,
+    pub visible: bool,
+}
+
+impl Default for GridConfig {
+    fn default() -> Self {
+        Self {
+            major_every: 5,
+            min_spacing: 8.0,
+            size: 20.0,
+            snap: true,
+            visible: true,
+        }
+    }
+}
+
+#[derive(Component)]
+struct Grid {
+    drawn: Option<(Rect, f32)>,
+    editor: Entity,
+    major: bool,
+}
+
+/// Moves a node so its top left corner lies on the closest grid intersection, halfway between
+/// two it moves right or up.
+pub(crate) fn snap_to_grid(position: Vec2, size: Vec2, cell: f32) -> Vec2 {
+    let corner = Vec2::new(-size.x, size.y) / 2.0;
+
+    // Unlike `round`, rounds halves the same way on both sides of the origin.
+    ((position + corner) / cell + 0.5).floor() * cell - corner
+}
+
+fn draw_grids(
+    config: Res<GridConfig>,
+    theme: Res<FlowNodeTheme>,
+    mut q_grids: Query<(&mut Grid, &mut Path, &mut Stroke, &mut Visibility)>,
+    q_editors: Query<(&Camera, &GlobalTransform, &OrthographicProjection)>,
+) {
+    for (mut grid, mut path, mut stroke, mut visibility) in q_grids.iter_mut() {
+        visibility.set_if_neq(if config.visible {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        });
+
+        let Ok((camera, camera_transform, projection)) = q_editors.get(grid.editor) else {
+            continue;
+        };
+
+        let pixel = world_per_pixel(camera, projection).x;
+        let mut spacing = config.size;
+
+        if grid.major {
+            spacing *= config.major_every.max(1) as f32;
+
+            // Major lines stay visible when zoomed out by skipping some of them.
+            while spacing / pixel < config.min_spacing {
+                spacing *= 2.0;
+            }
+        }
+
+        let center = camera_transform.translation().truncate();
+        let view = Rect::from_corners(projection.area.min + center, projection.area.max + center);
+
+        if grid.drawn == Some((view, spacing)) && !config.is_changed() && !theme.is_changed() {
+            continue;
+        }
+
+        let mut path_builder = PathBuilder::new();
+
+        if spacing / pixel >= config.min_spacing {
+            let min = (view.min / spacing).floor() * spacing;
+
+            for x in 0..=(view.width() / spacing).ceil() as i32 + 1 {
+                let x = min.x + x as f32 * spacing;
+
+                path_builder.move_to(Vec2::new(x, view.min.y));
+                path_builder.line_to(Vec2::new(x, view.max.y));
+            }
+
+            for y in 0..=(view.height() / spacing).ceil() as i32 + 1 {
+                let y = min.y + y as f32 * spacing;
+
+                path_builder.move_to(Vec2::new(view.min.x, y));
+                path_builder.line_to(Vec2::new(view.max.x, y));
+            }
+        }
+
+        *path = ShapePath::build_as(&path_builder.build());
+        stroke.color = if grid.major {
+            theme.grid_major
+        } else {
+            theme.grid_minor
+        };
+        stroke.options.line_width = pixel;
+        grid.drawn = Some((view, spacing));
+    }
+}
+
+fn spawn_grids(
+    mut commands: Commands,
+    theme: Res<FlowNodeTheme>,
+    q_editors: Query<Entity, With<CursorCamera>>,
+    q_grids: Query<(Entity, &Grid)>,
+) {
+    for (entity, grid) in q_grids.iter() {
+        if !q_editors.contains(grid.editor) {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+
+    for editor in q_editors.iter() {
+        if q_grids.iter().any(|(_, grid)| grid.editor == editor) {
+            continue;
+        }
+
+        for (major, color, z) in [
+            (false, theme.grid_minor, GRID_Z - 0.01),
+            (true, theme.grid_major, GRID_Z),
+        ] {
+            commands.spawn((
+                ShapeBundle {
+                    spatial: SpatialBundle::from_transform(Transform::from_xyz(0.0, 0.0, z)),
+                    ..default()
+                },
+                Stroke::new(color, 1.0),
+                Grid {
+                    drawn: None,
+                    editor,
+                    major,
+                },
+                InEditor(editor),
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cursor::tests::assert_near;
+
+    #[test]
+    fn snap_moves_top_left_corner_onto_grid() {
+        let size = Vec2::new(20.0, 20.0);
+
+        assert_near(snap_to_grid(Vec2::new(3.0, -4.0), size, 10.0), Vec2::ZERO);
+        assert_near(
+            snap_to_grid(Vec2::new(-23.0, -47.0), size, 10.0),
+            Vec2::new(-20.0, -50.0),
+        );
+    }
+
+    #[test]
+    fn snap_rounds_half_cells_up() {
+        let size = Vec2::new(20.0, 20.0);
+
+        // Corners at -15 and 15 both move up to the next line.
+        assert_near(
+            snap_to_grid(Vec2::new(-5.0, -25.0), size, 10.0),
+            Vec2::new(0.0, -20.0),
+        );
+        assert_near(
+            snap_to_grid(Vec2::new(25.0, 5.0), size, 10.0),
+            Vec2::new(30.0, 10.0),
+        );
+    }
+
+    #[test]
+    fn snap_keeps_odd_sized_nodes_off_grid_centres() {
+        let size = Vec2::new(35.0, 25.0);
+        let snapped = snap_to_grid(Vec2::ZERO, size, 10.0);
+        let corner = snapped + Vec2::new(-size.x, size.y) / 2.0;
+
+        assert_near(snapped, Vec2::new(-2.5, -2.5));
+        assert_near(corner, Vec2::new(-20.0, 10.0));
+    }
+}
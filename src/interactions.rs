@@ -1,12 +1,20 @@
-use bevy::prelude::*;
+use bevy::{prelude::*, window::PrimaryWindow};
 
-use crate::cursor::CursorPosition;
+use crate::{
+    bindings::{BindingInputs, FlowNodeBindings},
+    cursor::CursorPosition,
+    editor::{EditorPanel, EditorSet, InEditor},
+    focus::EditorFocus,
+};
 
 pub struct InteractionPlugin;
 
 impl Plugin for InteractionPlugin {
     fn build(&self, app: &mut App) {
-        app.add_event::<Clicked>().add_systems(Update, handle_click);
+        app.init_resource::<FlowNodeBindings>()
+            .init_resource::<EditorFocus>()
+            .add_event::<Clicked>()
+            .add_systems(Update, handle_click.in_set(EditorSet::Input));
     }
 }
 
@@ -17,7 +25,7 @@ pub enum Clickable {
 }
 
 impl Clickable {
-    fn clicked(&self, pos: Vec2, click_pos: Vec2) -> bool {
+    pub(crate) fn clicked(&self, pos: Vec2, click_pos: Vec2) -> bool {
         match *self {
             Self::Area(area) => {
                 let pos = pos - 0.5 * area;
@@ -40,21 +48,46 @@ impl Clickable {
 pub struct Clicked(pub Option<Entity>);
 
 fn handle_click(
+    bindings: Res<FlowNodeBindings>,
     cursor: Res<CursorPosition>,
-    mouse_button: Res<Input<MouseButton>>,
+    input: BindingInputs,
     mut ev_click: EventWriter<Clicked>,
-    query: Query<(Entity, &Clickable, &GlobalTransform)>,
+    ui_scale: Res<UiScale>,
+    query: Query<(Entity, &Clickable, &GlobalTransform, Option<&InEditor>)>,
+    q_primary: Query<Entity, With<PrimaryWindow>>,
+    q_ui: Query<(&Interaction, &Node, &GlobalTransform), Without<EditorPanel>>,
 ) {
-    if mouse_button.just_pressed(MouseButton::Left) {
+    // Clicks on UI drawn over the canvas, like menus, don't reach the nodes below. UI nodes are
+    // only laid out in the primary window.
+    let screen = Vec2::new(cursor.screen_x, cursor.screen_y);
+    let scale = ui_scale.0 as f32;
+    let over_ui = cursor.editor.is_some()
+        && cursor.window.is_some()
+        && cursor.window == q_primary.get_single().ok()
+        && q_ui.iter().any(|(interaction, node, transform)| {
+            let rect = node.logical_rect(transform);
+            let rect = Rect::from_corners(rect.min * scale, rect.max * scale);
+
+            *interaction != Interaction::None
+                && rect.contains(screen)
+                && !rect.intersect(cursor.editor_rect).is_empty()
+        });
+
+    if over_ui {
+        return;
+    }
+
+    if input.just_pressed(&bindings.select) {
         let click_pos = cursor.position();
         let mut clicked = query
             .iter()
-            .filter(|(_, clickable, transform)| {
-                clickable.clicked(transform.translation().truncate(), click_pos)
+            .filter(|(_, clickable, transform, editor)| {
+                editor.map_or(true, |editor| Some(editor.0) == cursor.editor)
+                    && clickable.clicked(transform.translation().truncate(), click_pos)
             })
             .collect::<Vec<_>>();
 
-        clicked.sort_by(|(_, _, a_transform), (_, _, b_transform)| {
+        clicked.sort_by(|(_, _, a_transform, _), (_, _, b_transform, _)| {
             a_transform
                 .translation()
                 .z
@@ -62,7 +95,7 @@ fn handle_click(
                 .unwrap()
         });
 
-        if let Some((entity, _, _)) = clicked.pop() {
+        if let Some((entity, _, _, _)) = clicked.pop() {
             ev_click.send(Clicked(Some(entity)));
         } else {
             ev_click.send(Clicked(None));
@@ -0,0 +1,539 @@
+#![allow(clippy::type_complexity)]
+
+use bevy::prelude::*;
+#[cfg(feature = "editor")]
+use std::{collections::HashMap, marker::PhantomData};
+
+#[cfg(feature = "editor")]
+use crate::{
+    editor::{EditorSet, InEditor},
+    interactions::Clickable,
+    node::{FlowNode, FlowNodeEvent, FlowNodeInput, FlowNodeOutput, Locked, Selected},
+    node_set::FlowNodeSet,
+};
+
+#[cfg(feature = "editor")]
+pub struct LayoutPlugin<N: FlowNodeSet>(PhantomData<N>);
+
+#[cfg(feature = "editor")]
+impl<N: FlowNodeSet> Default for LayoutPlugin<N> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+#[cfg(feature = "editor")]
+impl<N: FlowNodeSet> Plugin for LayoutPlugin<N> {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LayoutConfig>()
+            .add_event::<LayoutNodes>()
+            .add_systems(Update, layout_nodes::<N>.in_set(EditorSet::Layout));
+    }
+}
+
+#[derive(Clone, Resource)]
+pub struct LayoutConfig {
+    /// Horizontal space between layers.
+    pub layer_spacing: f32,
+    /// Vertical space between the nodes of a layer.
+    pub node_spacing: f32,
+    /// Passes spent reordering layers to remove wire crossings.
+    pub sweeps: usize,
+}
+
+impl Default for LayoutConfig {
+    fn default() -> Self {
+        Self {
+            layer_spacing: 80.0,
+            node_spacing: 30.0,
+            sweeps: 8,
+        }
+    }
+}
+
+/// Lays out the nodes of every editor from left to right, keeping the top left corner of the
+/// nodes in place. [`Locked`] nodes are left where they are.
+#[cfg(feature = "editor")]
+#[derive(Clone, Copy, Debug, Event, PartialEq, Eq)]
+pub enum LayoutNodes {
+    All,
+    /// Only the [`Selected`] nodes, wires to other nodes are ignored.
+    Selected,
+}
+
+/// A wire from output `from_port` of node `from` to input `to_port` of node `to`, ports are
+/// counted from the top.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LayoutEdge {
+    pub from: usize,
+    pub from_port: usize,
+    pub to: usize,
+    pub to_port: usize,
+}
+
+// A node of the layered graph, long wires pass through dummy nodes on every layer they cross.
+struct LayerItem {
+    layer: usize,
+    node: Option<usize>,
+}
+
+// A wire between neighbouring layers, the ports are where it leaves and enters as a share of
+// the node height.
+struct LayerEdge {
+    from: usize,
+    from_port: f32,
+    to: usize,
+    to_port: f32,
+}
+
+/// A layered (Sugiyama) layout of nodes with `sizes` connected by `edges`.
+///
+/// Returns the centre of every node, with the top left corner of the graph at the origin.
+pub fn layered_layout(sizes: &[Vec2], edges: &[LayoutEdge], config: &LayoutConfig) -> Vec<Vec2> {
+    let (items, centres) = layer_items(sizes, edges, config);
+    let mut positions = vec![Vec2::ZERO; sizes.len()];
+
+    for (item, centre) in items.iter().zip(centres) {
+        if let Some(node) = item.node {
+            positions[node] = centre;
+        }
+    }
+
+    positions
+}
+
+// Places the nodes and the dummy nodes of long wires, returning the centre of every item.
+fn layer_items(
+    sizes: &[Vec2],
+    edges: &[LayoutEdge],
+    config: &LayoutConfig,
+) -> (Vec<LayerItem>, Vec<Vec2>) {
+    let n = sizes.len();
+    let edges = edges
+        .iter()
+        .filter(|edge| edge.from != edge.to && edge.from < n && edge.to < n)
+        .collect::<Vec<_>>();
+
+    // Cycles are broken by reversing the edges that close them.
+    let mut reversed = vec![false; edges.len()];
+    let mut outgoing = vec![Vec::new(); n];
+    let mut state = vec![0u8; n];
+
+    for (i, edge) in edges.iter().enumerate() {
+        outgoing[edge.from].push(i);
+    }
+
+    for root in 0..n {
+        if state[root] != 0 {
+            continue;
+        }
+
+        let mut stack = vec![(root, 0)];
+
+        state[root] = 1;
+
+        while let Some((node, next)) = stack.pop() {
+            if let Some(&i) = outgoing[node].get(next) {
+                let to = edges[i].to;
+
+                stack.push((node, next + 1));
+
+                match state[to] {
+                    0 => {
+                        state[to] = 1;
+                        stack.push((to, 0));
+                    }
+                    1 => reversed[i] = true,
+                    _ => {}
+                }
+            } else {
+                state[node] = 2;
+            }
+        }
+    }
+
+    let acyclic = edges
+        .iter()
+        .zip(reversed.iter())
+        .map(|(edge, &reversed)| {
+            if reversed {
+                (edge.to, edge.from)
+            } else {
+                (edge.from, edge.to)
+            }
+        })
+        .collect::<Vec<_>>();
+
+    // Longest path layering, in topological order.
+    let mut incoming = vec![0; n];
+    let mut layers = vec![0; n];
+
+    for &(_, to) in acyclic.iter() {
+        incoming[to] += 1;
+    }
+
+    let mut ready = (0..n).filter(|&i| incoming[i] == 0).collect::<Vec<_>>();
+
+    while let Some(node) = ready.pop() {
+        for &(from, to) in acyclic.iter().filter(|(from, _)| *from == node) {
+            layers[to] = layers[to].max(layers[from] + 1);
+            incoming[to] -= 1;
+
+            if incoming[to] == 0 {
+                ready.push(to);
+            }
+        }
+    }
+
+    // Ports are spread over the height of their node.
+    let mut port_counts = vec![(0, 0); n];
+
+    for edge in edges.iter() {
+        port_counts[edge.from].1 = port_counts[edge.from].1.max(edge.from_port + 1);
+        port_counts[edge.to].0 = port_counts[edge.to].0.max(edge.to_port + 1);
+    }
+
+    let share = |port: usize, count: usize| (port + 1) as f32 / (count + 1) as f32;
+    let mut items = (0..n)
+        .map(|i| LayerItem {
+            layer: layers[i],
+            node: Some(i),
+        })
+        .collect::<Vec<_>>();
+    let mut layer_edges = Vec::new();
+
+    for ((edge, &(from, to)), &reversed) in edges.iter().zip(acyclic.iter()).zip(reversed.iter()) {
+        let (from_port, to_port) = if reversed {
+            (0.5, 0.5)
+        } else {
+            (
+                share(edge.from_port, port_counts[edge.from].1),
+                share(edge.to_port, port_counts[edge.to].0),
+            )
+        };
+        let mut previous = from;
+        let mut previous_port = from_port;
+
+        for layer in layers[from] + 1..layers[to] {
+            items.push(LayerItem { layer, node: None });
+            layer_edges.push(LayerEdge {
+                from: previous,
+                from_port: previous_port,
+                to: items.len() - 1,
+                to_port: 0.5,
+            });
+            previous = items.len() - 1;
+            previous_port = 0.5;
+        }
+
+        layer_edges.push(LayerEdge {
+            from: previous,
+            from_port: previous_port,
+            to,
+            to_port,
+        });
+    }
+
+    let layer_count = layers.iter().max().map_or(0, |max| max + 1);
+    let mut order = vec![Vec::new(); layer_count];
+
+    for (i, item) in items.iter().enumerate() {
+        order[item.layer].push(i);
+    }
+
+    // Barycentre sweeps, down then up, using where each wire meets its neighbour.
+    let mut index = vec![0.0; items.len()];
+
+    for _ in 0..config.sweeps {
+        for downwards in [true, false] {
+            let layer_order = if downwards {
+                (1..layer_count).collect::<Vec<_>>()
+            } else {
+                (0..layer_count.saturating_sub(1)).rev().collect()
+            };
+
+            for layer in layer_order {
+                for items in order.iter() {
+                    for (i, &item) in items.iter().enumerate() {
+                        index[item] = i as f32;
+                    }
+                }
+
+                let mut keys = order[layer]
+                    .iter()
+                    .map(|&item| {
+                        let neighbours = layer_edges
+                            .iter()
+                            .filter_map(|edge| match downwards {
+                                true if edge.to == item => Some(index[edge.from] + edge.from_port),
+                                false if edge.from == item => Some(index[edge.to] + edge.to_port),
+                                _ => None,
+                            })
+                            .collect::<Vec<_>>();
+                        let key = if neighbours.is_empty() {
+                            index[item] + 0.5
+                        } else {
+                            neighbours.iter().sum::<f32>() / neighbours.len() as f32
+                        };
+
+                        (item, key)
+                    })
+                    .collect::<Vec<_>>();
+
+                keys.sort_by(|a, b| a.1.total_cmp(&b.1));
+                order[layer] = keys.into_iter().map(|(item, _)| item).collect();
+            }
+        }
+    }
+
+    // Layers are as wide as their widest node and centred on each other vertically.
+    let size = |item: usize| items[item].node.map_or(Vec2::ZERO, |node| sizes[node]);
+    let heights = order
+        .iter()
+        .map(|layer_items| {
+            let nodes = layer_items.iter().map(|&item| size(item).y).sum::<f32>();
+
+            nodes + config.node_spacing * layer_items.len().saturating_sub(1) as f32
+        })
+        .collect::<Vec<_>>();
+    let total_height = heights.iter().copied().fold(0.0, f32::max);
+    let mut centres = vec![Vec2::ZERO; items.len()];
+    let mut x = 0.0;
+
+    for (layer_items, height) in order.iter().zip(heights.iter()) {
+        let width = layer_items
+            .iter()
+            .map(|&item| size(item).x)
+            .fold(0.0, f32::max);
+        let mut y = -(total_height - height) / 2.0;
+
+        for &item in layer_items.iter() {
+            let item_size = size(item);
+
+            centres[item] = Vec2::new(x + width / 2.0, y - item_size.y / 2.0);
+            y -= item_size.y + config.node_spacing;
+        }
+
+        x += width + config.layer_spacing;
+    }
+
+    (items, centres)
+}
+
+#[cfg(feature = "editor")]
+fn layout_nodes<N: FlowNodeSet>(
+    config: Res<LayoutConfig>,
+    mut ev_layout: EventReader<LayoutNodes>,
+    mut ev_node: EventWriter<FlowNodeEvent<N>>,
+    mut q_nodes: Query<
+        (
+            Entity,
+            &mut Transform,
+            &Clickable,
+            &InEditor,
+            &Children,
+            Has<Locked>,
+            Has<Selected>,
+        ),
+        With<FlowNode<N>>,
+    >,
+    q_inputs: Query<&FlowNodeInput<N>>,
+    q_outputs: Query<(), With<FlowNodeOutput>>,
+) {
+    for ev in ev_layout.read() {
+        let mut editors = HashMap::<Entity, Vec<(Entity, Rect)>>::new();
+        let mut outputs = HashMap::new();
+
+        for (entity, transform, clickable, editor, children, locked, selected) in q_nodes.iter() {
+            if locked || *ev == LayoutNodes::Selected && !selected {
+                continue;
+            }
+
+            if let Clickable::Area(size) = clickable {
+                editors.entry(editor.0).or_default().push((
+                    entity,
+                    Rect::from_center_size(transform.translation.truncate(), *size),
+                ));
+            }
+
+            let ports = children.iter().filter(|child| q_outputs.contains(**child));
+
+            for (port, output) in ports.enumerate() {
+                outputs.insert(*output, (entity, port));
+            }
+        }
+
+        for nodes in editors.into_values() {
+            let indices = nodes
+                .iter()
+                .enumerate()
+                .map(|(i, (entity, _))| (*entity, i))
+                .collect::<HashMap<_, _>>();
+            let mut edges = Vec::new();
+
+            for (to, (entity, _)) in nodes.iter().enumerate() {
+                let Ok((_, _, _, _, children, ..)) = q_nodes.get(*entity) else {
+                    continue;
+                };
+                let inputs = children
+                    .iter()
+                    .filter_map(|child| q_inputs.get(*child).ok());
+
+                for (to_port, input) in inputs.enumerate() {
+                    let Some((node, from_port)) =
+                        input.connection.and_then(|output| outputs.get(&output))
+                    else {
+                        continue;
+                    };
+
+                    if let Some(&from) = indices.get(node) {
+                        edges.push(LayoutEdge {
+                            from,
+                            from_port: *from_port,
+                            to,
+                            to_port,
+                        });
+                    }
+                }
+            }
+
+            let sizes = nodes
+                .iter()
+                .map(|(_, rect)| rect.size())
+                .collect::<Vec<_>>();
+            let positions = layered_layout(&sizes, &edges, &config);
+            let bounds = nodes
+                .iter()
+                .fold(nodes[0].1, |bounds, (_, rect)| bounds.union(*rect));
+            let corner = Vec2::new(bounds.min.x, bounds.max.y);
+
+            for ((entity, _), position) in nodes.iter().zip(positions) {
+                if let Ok((_, mut transform, ..)) = q_nodes.get_mut(*entity) {
+                    let from = transform.translation.truncate();
+                    let to = corner + position;
+
+                    transform.translation.x = to.x;
+                    transform.translation.y = to.y;
+
+                    if to != from {
+                        ev_node.send(FlowNodeEvent::Moved {
+                            entity: *entity,
+                            from,
+                            to,
+                        });
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SIZE: Vec2 = Vec2::new(100.0, 50.0);
+
+    fn edge(from: usize, to: usize) -> LayoutEdge {
+        LayoutEdge {
+            from,
+            from_port: 0,
+            to,
+            to_port: 0,
+        }
+    }
+
+    fn rect(centre: Vec2, size: Vec2) -> Rect {
+        Rect::from_center_size(centre, size)
+    }
+
+    #[test]
+    fn chain_moves_right_per_layer() {
+        let positions = layered_layout(
+            &[SIZE; 3],
+            &[edge(1, 2), edge(0, 1)],
+            &LayoutConfig::default(),
+        );
+
+        assert!(positions[0].x < positions[1].x);
+        assert!(positions[1].x < positions[2].x);
+        assert_eq!(positions[1].x - positions[0].x, SIZE.x + 80.0);
+    }
+
+    #[test]
+    fn cycle_places_every_node() {
+        let sizes = [SIZE; 3];
+        let positions = layered_layout(
+            &sizes,
+            &[edge(0, 1), edge(1, 2), edge(2, 0)],
+            &LayoutConfig::default(),
+        );
+
+        assert_eq!(positions.len(), 3);
+
+        for (i, a) in positions.iter().enumerate() {
+            assert!(a.is_finite());
+
+            for b in positions.iter().skip(i + 1) {
+                assert!(rect(*a, SIZE).intersect(rect(*b, SIZE)).is_empty());
+            }
+        }
+    }
+
+    #[test]
+    fn long_edge_passes_beside_nodes() {
+        let sizes = [SIZE, Vec2::new(100.0, 200.0), SIZE];
+        let (items, centres) = layer_items(
+            &sizes,
+            &[edge(0, 1), edge(1, 2), edge(0, 2)],
+            &LayoutConfig::default(),
+        );
+        let dummy = items.iter().position(|item| item.node.is_none()).unwrap();
+        let node = rect(centres[1], sizes[1]);
+
+        assert_eq!(items.len(), 4);
+        assert_eq!(items[dummy].layer, 1);
+        assert!(centres[dummy].y < node.min.y || centres[dummy].y > node.max.y);
+    }
+
+    #[test]
+    fn port_order_removes_crossings() {
+        // Output 0 of node 0 feeds node 2 and output 1 feeds node 1, so node 2 belongs on top.
+        let edges = [
+            LayoutEdge {
+                from: 0,
+                from_port: 0,
+                to: 2,
+                to_port: 0,
+            },
+            LayoutEdge {
+                from: 0,
+                from_port: 1,
+                to: 1,
+                to_port: 0,
+            },
+        ];
+        let positions = layered_layout(&[SIZE; 3], &edges, &LayoutConfig::default());
+
+        assert!(positions[2].y > positions[1].y);
+
+        // Inputs work the same way when sweeping back up.
+        let edges = [
+            LayoutEdge {
+                from: 0,
+                from_port: 0,
+                to: 2,
+                to_port: 1,
+            },
+            LayoutEdge {
+                from: 1,
+                from_port: 0,
+                to: 2,
+                to_port: 0,
+            },
+        ];
+        let positions = layered_layout(&[SIZE; 3], &edges, &LayoutConfig::default());
+
+        assert!(positions[1].y > positions[0].y);
+    }
+}
@@ -1,37 +1,132 @@
+#[cfg(feature = "editor")]
 use bevy::app::{PluginGroup, PluginGroupBuilder};
+#[cfg(feature = "editor")]
 use std::marker::PhantomData;
 
+#[cfg(feature = "editor")]
+pub mod align;
+#[cfg(feature = "editor")]
 pub mod assets;
+#[cfg(feature = "editor")]
+pub mod bindings;
+#[cfg(feature = "editor")]
+pub mod builder;
+#[cfg(feature = "editor")]
 pub mod camera;
+#[cfg(feature = "editor")]
 pub mod connection;
+#[cfg(feature = "editor")]
 pub mod cursor;
+#[cfg(feature = "editor")]
+pub mod editor;
+pub mod evaluation;
+#[cfg(feature = "editor")]
+pub mod execution;
+#[cfg(feature = "editor")]
+pub mod focus;
+#[cfg(feature = "editor")]
+pub mod graph;
+#[cfg(feature = "editor")]
+pub mod grid;
+#[cfg(feature = "editor")]
 pub mod interactions;
+pub mod layout;
+#[cfg(feature = "editor")]
 pub mod menu;
+#[cfg(feature = "editor")]
+pub mod minimap;
+#[cfg(feature = "editor")]
 pub mod node;
+pub mod node_set;
+#[cfg(feature = "editor")]
+pub mod notice;
+pub mod runtime;
+#[cfg(feature = "editor")]
 pub mod template;
+#[cfg(feature = "editor")]
+pub mod theme;
+#[cfg(feature = "editor")]
 pub mod widget;
+#[cfg(feature = "editor")]
 pub mod widgets;
 
+#[cfg(feature = "editor")]
 pub use crate::{
-    camera::PanCameraPlugin,
+    align::{AlignConfig, AlignNodes, AlignPlugin},
+    bindings::{Binding, FlowNodeBindings},
+    builder::{FlowNodeCommands, NodeHandle},
+    camera::{CameraTarget, PanCameraConfig, PanCameraPlugin},
+    connection::{ConnectionEvent, ConnectionInfo},
     cursor::CursorCamera,
+    editor::{
+        editor_editable,
+        editor_enabled,
+        EditorMode,
+        EditorPanel,
+        EditorPlugin,
+        EditorSet,
+        InEditor,
+    },
+    execution::{EventNode, ExecTrigger},
+    focus::EditorFocus,
+    graph::{FlowNodeGraph, FlowNodeState, ResetNodeState, SaveError},
+    grid::{GridConfig, GridPlugin},
+    layout::{LayoutNodes, LayoutPlugin},
     menu::{FlowNodeMenu, FlowNodeMenuPlugin},
-    node::{FlowNode, FlowNodeEvent, FlowNodeInput, FlowNodeOutput, FlowNodeSet},
+    minimap::{MinimapConfig, MinimapPlugin},
+    node::{
+        FlowNode,
+        FlowNodeEvent,
+        FlowNodeInput,
+        FlowNodeOutput,
+        FlowNodeTitle,
+        Locked,
+        PortKind,
+        PortShape,
+        PortStyle,
+        Selected,
+    },
+    notice::{ActionRejected, NoticeConfig},
     template::{FlowNodeSlot, FlowNodeTemplate},
+    theme::FlowNodeTheme,
     widget::{SlotWidget, Widget, WidgetPlugin},
 };
+pub use crate::{
+    evaluation::{
+        EvalContext,
+        EvaluateNodes,
+        EvaluationMode,
+        InputSource,
+        MissingInput,
+        NodeInputs,
+    },
+    layout::{layered_layout, LayoutConfig, LayoutEdge},
+    node_set::{ExecFlow, FlowNodeSet},
+    runtime::{
+        FindNodeError,
+        FlowNodeRuntimePlugin,
+        RuntimeGraph,
+        RuntimeSet,
+        SavedConnection,
+        SavedGraph,
+        SavedNode,
+    },
+};
 
+/// Everything needed to edit nodes of `N`, can be added once per [`FlowNodeSet`].
+#[cfg(feature = "editor")]
 #[derive(Default)]
 pub struct FlowNodePlugins<N: FlowNodeSet>(PhantomData<N>);
 
+#[cfg(feature = "editor")]
 impl<N: FlowNodeSet> PluginGroup for FlowNodePlugins<N> {
     fn build(self) -> PluginGroupBuilder {
         PluginGroupBuilder::start::<Self>()
-            .add(assets::DefaultAssetsPlugin)
-            .add(connection::ConnectionPlugin::<N>::default())
-            .add(cursor::CursorPlugin)
-            .add(interactions::InteractionPlugin)
             .add(node::FlowNodePlugin::<N>::default())
+            .add(connection::ConnectionPlugin::<N>::default())
+            .add(execution::ExecutionPlugin::<N>::default())
             .add(template::FlowNodeTemplatePlugin::<N>::default())
+            .add(layout::LayoutPlugin::<N>::default())
+            .add(builder::BuilderPlugin::<N>::default())
     }
 }
@@ -1,25 +1,45 @@
-use bevy::{ecs::system::Resource, prelude::*};
-use std::marker::PhantomData;
+use bevy::{ecs::system::Resource, prelude::*, window::PrimaryWindow};
+use std::{marker::PhantomData, slice};
 
-use crate::{assets::DefaultAssets, cursor::CursorPosition, node::FlowNodeSet};
+use crate::{
+    assets::DefaultAssets,
+    bindings::{BindingInput, BindingInputs, FlowNodeBindings},
+    cursor::CursorPosition,
+    editor::{editor_editable, EditorSet},
+    focus::EditorFocus,
+    node_set::FlowNodeSet,
+    theme::FlowNodeTheme,
+};
 
+/// Opens a menu of nodes to build at the cursor.
+///
+/// Menus are UI, which Bevy only lays out in the primary window, so they don't open in editors
+/// shown in other windows.
 #[derive(Default)]
 pub struct FlowNodeMenuPlugin<M: FlowNodeMenu<N>, N: FlowNodeSet>(PhantomData<(M, N)>);
 
 impl<M: FlowNodeMenu<N>, N: FlowNodeSet> Plugin for FlowNodeMenuPlugin<M, N> {
     fn build(&self, app: &mut App) {
-        app.insert_resource(M::default())
-            .insert_resource(MenuConfig::default())
+        app.init_resource::<FlowNodeBindings>()
+            .init_resource::<EditorFocus>()
+            .init_resource::<FlowNodeTheme>()
+            .insert_resource(M::default())
+            .init_resource::<MenuConfig>()
             .add_event::<MenuEvent<N>>()
             .add_systems(Startup, setup)
             .add_systems(
                 Update,
                 (
-                    build_from_menu_select::<M, N>,
-                    close_menu,
-                    hover_menu_option::<N>,
-                    open_menu::<M, N>,
-                    select_menu_option::<N>.before(close_menu),
+                    apply_menu_theme::<N>.in_set(EditorSet::Render),
+                    (
+                        build_from_menu_select::<M, N>,
+                        close_menu,
+                        focus_menu,
+                        hover_menu_option::<N>,
+                        open_menu::<M, N>.run_if(editor_editable),
+                        select_menu_option::<N>.before(close_menu),
+                    )
+                        .in_set(EditorSet::Interaction),
                 ),
             );
     }
@@ -37,8 +57,6 @@ struct Menu;
 
 #[derive(Resource)]
 pub struct MenuConfig {
-    pub color: Color,
-    pub color_hover: Color,
     pub font_size: f32,
     pub option_height: f32,
     pub width: f32,
@@ -47,8 +65,6 @@ pub struct MenuConfig {
 impl Default for MenuConfig {
     fn default() -> Self {
         Self {
-            color: Color::rgb(0.1, 0.1, 0.1),
-            color_hover: Color::rgb(0.3, 0.3, 0.3),
             font_size: 16.0,
             option_height: 20.0,
             width: 150.0,
@@ -71,51 +87,116 @@ struct MenuResources {
     text_style: TextStyle,
 }
 
-fn setup(mut commands: Commands, assets: Res<DefaultAssets>, config: Res<MenuConfig>) {
+fn setup(
+    mut commands: Commands,
+    assets: Res<DefaultAssets>,
+    config: Res<MenuConfig>,
+    theme: Res<FlowNodeTheme>,
+) {
     let text_style = TextStyle {
         font: assets.font.clone(),
         font_size: config.font_size,
-        color: Color::WHITE,
+        color: theme.menu_text,
     };
 
     commands.insert_resource(MenuResources { text_style });
 }
 
+fn apply_menu_theme<N: FlowNodeSet>(
+    theme: Res<FlowNodeTheme>,
+    mut res: ResMut<MenuResources>,
+    mut q_options: Query<(&Interaction, &mut BackgroundColor), With<MenuOption<N>>>,
+    mut q_text: Query<(&Parent, &mut Text)>,
+) {
+    if !theme.is_changed() {
+        return;
+    }
+
+    res.text_style.color = theme.menu_text;
+
+    for (interaction, mut color) in q_options.iter_mut() {
+        *color = match interaction {
+            Interaction::Hovered => theme.menu_hover.into(),
+            _ => theme.menu.into(),
+        };
+    }
+
+    for (parent, mut text) in q_text.iter_mut() {
+        if q_options.contains(parent.get()) {
+            for section in text.sections.iter_mut() {
+                section.style.color = theme.menu_text;
+            }
+        }
+    }
+}
+
 fn close_menu(
     mut commands: Commands,
-    keys: Res<Input<KeyCode>>,
-    mouse: Res<Input<MouseButton>>,
+    bindings: Res<FlowNodeBindings>,
+    input: BindingInputs,
     q_menu: Query<Entity, With<Menu>>,
 ) {
-    if mouse.just_released(MouseButton::Left)
-        || mouse.just_released(MouseButton::Right)
-        || keys.just_pressed(KeyCode::A)
-    {
+    let close = bindings.close_menu.iter().any(|binding| {
+        let binding = slice::from_ref(binding);
+
+        match binding[0].input {
+            BindingInput::Key(_) => input.just_pressed_in(EditorFocus::Menu, binding),
+            BindingInput::Mouse(_) => input.just_released(binding),
+        }
+    });
+
+    if close {
         for entity in q_menu.iter() {
             commands.entity(entity).despawn_recursive();
         }
     }
 }
 
+fn focus_menu(
+    mut focus: ResMut<EditorFocus>,
+    mut removed: RemovedComponents<Menu>,
+    q_added: Query<(), Added<Menu>>,
+) {
+    if !q_added.is_empty() {
+        *focus = EditorFocus::Menu;
+    } else if removed.read().next().is_some() && *focus == EditorFocus::Menu {
+        *focus = EditorFocus::Canvas;
+    }
+}
+
 fn open_menu<M: FlowNodeMenu<N>, N: FlowNodeSet>(
     mut commands: Commands,
+    bindings: Res<FlowNodeBindings>,
     config: Res<MenuConfig>,
     cursor: Res<CursorPosition>,
-    keys: Res<Input<KeyCode>>,
+    input: BindingInputs,
     menu: Res<M>,
     res: Res<MenuResources>,
+    theme: Res<FlowNodeTheme>,
+    q_primary: Query<(), With<PrimaryWindow>>,
 ) {
-    if keys.just_pressed(KeyCode::A) {
+    let in_primary = cursor
+        .window
+        .is_some_and(|window| q_primary.contains(window));
+
+    if cursor.editor.is_some() && in_primary && input.just_pressed(&bindings.open_menu) {
         let options = menu.options();
         let height = config.option_height * options.len() as f32;
+        // Keep the menu inside the editor it was opened in.
+        let rect = cursor.editor_rect;
+        let left = cursor
+            .screen_x
+            .min(rect.max.x - config.width)
+            .max(rect.min.x);
+        let top = cursor.screen_y.min(rect.max.y - height).max(rect.min.y);
 
         commands
             .spawn(NodeBundle {
                 style: Style {
                     flex_direction: FlexDirection::Column,
                     position_type: PositionType::Absolute,
-                    left: Val::Px(cursor.screen_x),
-                    top: Val::Px(cursor.screen_y),
+                    left: Val::Px(left),
+                    top: Val::Px(top),
                     width: Val::Px(config.width),
                     height: Val::Px(height),
                     ..default()
@@ -133,7 +214,7 @@ fn open_menu<M: FlowNodeMenu<N>, N: FlowNodeSet>(
                                 align_items: AlignItems::Center,
                                 ..default()
                             },
-                            background_color: config.color.into(),
+                            background_color: theme.menu.into(),
                             ..default()
                         })
                         .with_children(|parent| {
@@ -163,7 +244,7 @@ fn select_menu_option<N: FlowNodeSet>(
 }
 
 fn hover_menu_option<N: FlowNodeSet>(
-    config: Res<MenuConfig>,
+    theme: Res<FlowNodeTheme>,
     mut q_options: Query<
         (&Interaction, &mut BackgroundColor),
         (Changed<Interaction>, With<MenuOption<N>>),
@@ -172,10 +253,10 @@ fn hover_menu_option<N: FlowNodeSet>(
     for (interaction, mut color) in q_options.iter_mut() {
         match interaction {
             Interaction::Hovered => {
-                *color = config.color_hover.into();
+                *color = theme.menu_hover.into();
             }
             _ => {
-                *color = config.color.into();
+                *color = theme.menu.into();
             }
         }
     }
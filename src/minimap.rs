@@ -0,0 +1,306 @@
+#![allow(clippy::type_complexity)]
+
+use bevy::{prelude::*, render::camera::NormalizedRenderTarget, window::PrimaryWindow};
+
+use crate::{
+    camera::{CameraTarget, PanCamera},
+    cursor::CursorPosition,
+    editor::{EditorSet, InEditor},
+    interactions::Clickable,
+    theme::FlowNodeTheme,
+};
+
+/// Shows an overview of the canvas in the corner of every [`PanCamera`] editor, pressing or
+/// dragging in it recentres the camera.
+pub struct MinimapPlugin;
+
+impl Plugin for MinimapPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<FlowNodeTheme>()
+            .init_resource::<MinimapConfig>()
+            .add_systems(
+                Update,
+                (
+                    navigate_minimap.in_set(EditorSet::Interaction),
+                    spawn_minimaps.in_set(EditorSet::Layout),
+                    update_minimaps.in_set(EditorSet::Render),
+                ),
+            );
+    }
+}
+
+#[derive(Resource)]
+pub struct MinimapConfig {
+    pub margin: f32,
+    /// Space kept free around the graph inside the minimap.
+    pub padding: f32,
+    pub size: Vec2,
+}
+
+impl Default for MinimapConfig {
+    fn default() -> Self {
+        Self {
+            margin: 10.0,
+            padding: 8.0,
+            size: Vec2::new(200.0, 150.0),
+        }
+    }
+}
+
+/// Maps the canvas area in `bounds` onto the minimap.
+#[derive(Component)]
+struct Minimap {
+    bounds: Rect,
+    editor: Entity,
+    offset: Vec2,
+    scale: f32,
+}
+
+impl Minimap {
+    fn to_local(&self, world: Vec2) -> Vec2 {
+        let position = Vec2::new(world.x - self.bounds.min.x, self.bounds.max.y - world.y);
+
+        self.offset + position * self.scale
+    }
+
+    fn to_world(&self, local: Vec2) -> Vec2 {
+        let position = (local - self.offset) / self.scale;
+
+        Vec2::new(
+            self.bounds.min.x + position.x,
+            self.bounds.max.y - position.y,
+        )
+    }
+}
+
+#[derive(Component)]
+struct MinimapNode;
+
+#[derive(Component)]
+struct MinimapView;
+
+fn absolute(position: Vec2, size: Vec2) -> Style {
+    Style {
+        position_type: PositionType::Absolute,
+        left: Val::Px(position.x),
+        top: Val::Px(position.y),
+        width: Val::Px(size.x),
+        height: Val::Px(size.y),
+        ..default()
+    }
+}
+
+// UI nodes are only laid out in the primary window, editors drawn anywhere else have no
+// minimap.
+fn in_primary_window(camera: &Camera, primary: Option<Entity>) -> bool {
+    matches!(
+        camera.target.normalize(primary),
+        Some(NormalizedRenderTarget::Window(window_ref)) if Some(window_ref.entity()) == primary
+    )
+}
+
+fn navigate_minimap(
+    mut commands: Commands,
+    cursor: Res<CursorPosition>,
+    q_primary: Query<Entity, With<PrimaryWindow>>,
+    q_minimaps: Query<(&Minimap, &Interaction, &Node, &GlobalTransform)>,
+    mut q_cameras: Query<(&Camera, &mut Transform), With<PanCamera>>,
+) {
+    let primary = q_primary.get_single().ok();
+
+    // The cursor is relative to the window it is in.
+    if cursor.window.is_none() || cursor.window != primary {
+        return;
+    }
+
+    for (minimap, interaction, node, transform) in q_minimaps.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        let Ok((camera, mut camera_transform)) = q_cameras.get_mut(minimap.editor) else {
+            continue;
+        };
+
+        if !in_primary_window(camera, primary) {
+            continue;
+        }
+
+        let local = Vec2::new(cursor.screen_x, cursor.screen_y) - node.logical_rect(transform).min;
+        let target = minimap.to_world(local);
+
+        camera_transform.translation.x = target.x;
+        camera_transform.translation.y = target.y;
+        commands.entity(minimap.editor).remove::<CameraTarget>();
+    }
+}
+
+fn spawn_minimaps(
+    mut commands: Commands,
+    theme: Res<FlowNodeTheme>,
+    mut removed: RemovedComponents<PanCamera>,
+    q_added: Query<Entity, Added<PanCamera>>,
+    q_minimaps: Query<(Entity, &Minimap)>,
+) {
+    for editor in removed.read() {
+        for (entity, minimap) in q_minimaps.iter() {
+            if minimap.editor == editor {
+                commands.entity(entity).despawn_recursive();
+            }
+        }
+    }
+
+    for editor in q_added.iter() {
+        commands
+            .spawn((
+                NodeBundle {
+                    background_color: theme.minimap.into(),
+                    ..default()
+                },
+                Interaction::default(),
+                Minimap {
+                    bounds: Rect::default(),
+                    editor,
+                    offset: Vec2::ZERO,
+                    scale: 1.0,
+                },
+            ))
+            .with_children(|parent| {
+                parent.spawn((
+                    NodeBundle {
+                        border_color: theme.minimap_view.into(),
+                        z_index: ZIndex::Local(1),
+                        ..default()
+                    },
+                    MinimapView,
+                ));
+            });
+    }
+}
+
+fn update_minimaps(
+    mut commands: Commands,
+    config: Res<MinimapConfig>,
+    theme: Res<FlowNodeTheme>,
+    mut q_minimaps: Query<(
+        Entity,
+        &mut Minimap,
+        &Interaction,
+        &mut Style,
+        &mut BackgroundColor,
+        &Children,
+    )>,
+    mut q_views: Query<(&mut Style, &mut BorderColor), (With<MinimapView>, Without<Minimap>)>,
+    mut q_rects: Query<
+        (&mut Style, &mut BackgroundColor),
+        (With<MinimapNode>, Without<Minimap>, Without<MinimapView>),
+    >,
+    q_editors: Query<(&Camera, &GlobalTransform, &OrthographicProjection)>,
+    q_nodes: Query<(&Clickable, &GlobalTransform, &InEditor), Without<Parent>>,
+    q_primary: Query<Entity, With<PrimaryWindow>>,
+) {
+    let primary = q_primary.get_single().ok();
+
+    for (entity, mut minimap, interaction, mut style, mut background, children) in
+        q_minimaps.iter_mut()
+    {
+        let Ok((camera, camera_transform, projection)) = q_editors.get(minimap.editor) else {
+            continue;
+        };
+        let viewport = camera
+            .logical_viewport_rect()
+            .filter(|_| in_primary_window(camera, primary));
+        let Some(viewport) = viewport else {
+            style.set_if_neq(Style {
+                display: Display::None,
+                ..default()
+            });
+            continue;
+        };
+
+        let center = camera_transform.translation().truncate();
+        let view = Rect::from_corners(projection.area.min + center, projection.area.max + center);
+        let nodes = q_nodes
+            .iter()
+            .filter_map(|(clickable, transform, editor)| match clickable {
+                Clickable::Area(size) if editor.0 == minimap.editor => Some(
+                    Rect::from_center_size(transform.translation().truncate(), *size),
+                ),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        // The mapping stays fixed while dragging so the view does not run away from the cursor.
+        if *interaction != Interaction::Pressed {
+            let bounds = nodes.iter().fold(view, |bounds, node| bounds.union(*node));
+            let inner = config.size - 2.0 * config.padding;
+
+            minimap.bounds = bounds;
+            minimap.scale = (inner / bounds.size()).min_element();
+            minimap.offset = config.padding + 0.5 * (inner - bounds.size() * minimap.scale);
+        }
+
+        style.set_if_neq(Style {
+            overflow: Overflow::clip(),
+            ..absolute(viewport.max - config.size - config.margin, config.size)
+        });
+
+        let mut rects = children
+            .iter()
+            .filter(|child| q_rects.contains(**child))
+            .copied()
+            .collect::<Vec<_>>();
+
+        for child in rects.drain(nodes.len().min(rects.len())..) {
+            commands.entity(child).despawn_recursive();
+        }
+
+        for (i, node) in nodes.iter().enumerate() {
+            let node_style = absolute(
+                minimap.to_local(Vec2::new(node.min.x, node.max.y)),
+                node.size() * minimap.scale,
+            );
+
+            if let Some(Ok((mut rect_style, mut rect_background))) =
+                rects.get(i).map(|child| q_rects.get_mut(*child))
+            {
+                rect_style.set_if_neq(node_style);
+
+                if theme.is_changed() {
+                    *rect_background = theme.minimap_node.into();
+                }
+            } else {
+                commands.entity(entity).with_children(|parent| {
+                    parent.spawn((
+                        NodeBundle {
+                            style: node_style,
+                            background_color: theme.minimap_node.into(),
+                            ..default()
+                        },
+                        MinimapNode,
+                    ));
+                });
+            }
+        }
+
+        for child in children.iter() {
+            if let Ok((mut view_style, mut border)) = q_views.get_mut(*child) {
+                view_style.set_if_neq(Style {
+                    border: UiRect::all(Val::Px(1.0)),
+                    ..absolute(
+                        minimap.to_local(Vec2::new(view.min.x, view.max.y)),
+                        view.size() * minimap.scale,
+                    )
+                });
+
+                if theme.is_changed() {
+                    *border = theme.minimap_view.into();
+                }
+            }
+        }
+
+        if theme.is_changed() {
+            *background = theme.minimap.into();
+        }
+    }
+}
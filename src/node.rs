@@ -5,29 +5,26 @@ use bevy::{
     render::render_resource::{AsBindGroup, ShaderRef},
     sprite::{Material2d, Material2dPlugin},
 };
-use std::{collections::HashMap, marker::PhantomData};
+use std::marker::PhantomData;
 
 use crate::{
+    align::{AlignConfig, AlignmentGuides},
     assets::DefaultAssets,
-    connection::ConnectionEvent,
-    cursor::CursorPosition,
-    interactions::Clicked,
-    template::FlowNodeTemplate,
+    bindings::{BindingInputs, FlowNodeBindings},
+    connection::{ConnectionEvent, ConnectionInfo},
+    cursor::{world_per_pixel, CursorPosition},
+    editor::{editor_editable, EditorPlugin, EditorSet, InEditor},
+    evaluation::{EvaluateNodes, EvaluationMode},
+    graph::{reset_node_states, resolve_output_nodes, ResetNodeState},
+    grid::{snap_to_grid, GridConfig},
+    interactions::{Clickable, Clicked},
+    node_set::FlowNodeSet,
+    notice::ActionRejected,
+    theme::{mix, FlowNodeTheme},
 };
 
 const NODE_SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(7843551199445678407);
 
-pub trait FlowNodeSet: 'static + Clone + Default + Sized + Send + Sync {
-    type NodeIO: Send + Sync;
-
-    fn resolve(
-        &self,
-        inputs: HashMap<String, Option<Self::NodeIO>>,
-        output: Option<&str>,
-    ) -> Self::NodeIO;
-    fn template(self) -> FlowNodeTemplate<Self>;
-}
-
 pub struct FlowNodePlugin<N: FlowNodeSet>(PhantomData<N>);
 
 impl<N: FlowNodeSet> Default for FlowNodePlugin<N> {
@@ -37,6 +34,40 @@ impl<N: FlowNodeSet> Default for FlowNodePlugin<N> {
 }
 
 impl<N: FlowNodeSet> Plugin for FlowNodePlugin<N> {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<SharedNodePlugin>() {
+            app.add_plugins(SharedNodePlugin);
+        }
+
+        app.init_resource::<EvaluationMode>()
+            .add_event::<EvaluateNodes>()
+            .add_event::<FlowNodeEvent<N>>()
+            .add_event::<ResetNodeState>()
+            .add_systems(
+                Update,
+                (
+                    (
+                        (delete_node::<N>, drag_node::<N>.after(activate_node))
+                            .run_if(editor_editable),
+                        report_selection::<N>,
+                    )
+                        .in_set(EditorSet::Interaction),
+                    (
+                        reset_node_states::<N>,
+                        resolve_output_nodes::<N>.after(reset_node_states::<N>),
+                    )
+                        .in_set(EditorSet::Evaluate),
+                    (apply_theme::<N>, rename_nodes::<N>).in_set(EditorSet::Render),
+                ),
+            );
+    }
+}
+
+// Shared by the plugins of every node set, kept apart from `EditorPlugin` which apps may add
+// on their own.
+struct SharedNodePlugin;
+
+impl Plugin for SharedNodePlugin {
     fn build(&self, app: &mut App) {
         load_internal_asset!(
             app,
@@ -44,23 +75,26 @@ impl<N: FlowNodeSet> Plugin for FlowNodePlugin<N> {
             "assets/shaders/node.wgsl",
             Shader::from_wgsl
         );
-        app.insert_resource(FlowNodeConfig::default())
-            .add_event::<FlowNodeEvent<N>>()
-            .add_plugins(Material2dPlugin::<FlowNodeMaterial>::default())
+
+        if !app.is_plugin_added::<EditorPlugin>() {
+            app.add_plugins(EditorPlugin);
+        }
+
+        app.add_plugins(Material2dPlugin::<FlowNodeMaterial>::default())
+            .init_resource::<FlowNodeConfig>()
             .add_systems(Startup, setup)
             .add_systems(
                 Update,
                 (
-                    activate_node,
-                    delete_node::<N>,
-                    drag_node::<N>.after(activate_node),
-                    resolve_output_nodes::<N>,
+                    (activate_node, toggle_selection).in_set(EditorSet::Interaction),
+                    highlight_selected.in_set(EditorSet::Render),
                 ),
             );
     }
 }
 
-#[derive(Default, Resource)]
+/// The node of an editor being dragged, and where the next spawned or selected node is layered.
+#[derive(Component, Default)]
 pub struct ActiveNode {
     pub count: u32,
     pub index: f32,
@@ -69,51 +103,26 @@ pub struct ActiveNode {
     pub offset: Vec2,
 }
 
-#[derive(Component, Default, Deref, DerefMut)]
-pub struct FlowNode<N: FlowNodeSet>(pub(crate) N);
-
-impl<N: FlowNodeSet> FlowNode<N> {
-    pub fn get_inputs(
-        &self,
-        entity: Entity,
-        q_nodes: &Query<(Entity, &FlowNode<N>), Without<OutputNode>>,
-        q_inputs: &Query<(&Parent, &FlowNodeInput<N>)>,
-        q_outputs: &Query<(&Parent, &FlowNodeOutput)>,
-    ) -> HashMap<String, Option<N::NodeIO>> {
-        let mut inputs = HashMap::new();
-
-        for (parent, input) in q_inputs.iter() {
-            if parent.get() == entity {
-                inputs.insert(
-                    input.label.clone(),
-                    input.get_input(q_nodes, q_inputs, q_outputs),
-                );
-            }
-        }
+/// Marks the nodes selected in their editor, the active node is always one of them.
+///
+/// Dragging or deleting the active node drags or deletes the whole selection.
+#[derive(Component)]
+pub struct Selected;
 
-        inputs
-    }
+/// Protects a node from being moved, deleted or unplugged by the user.
+#[derive(Component)]
+pub struct Locked;
 
-    pub fn resolve(
-        &self,
-        entity: Entity,
-        output: Option<&str>,
-        q_nodes: &Query<(Entity, &FlowNode<N>), Without<OutputNode>>,
-        q_inputs: &Query<(&Parent, &FlowNodeInput<N>)>,
-        q_outputs: &Query<(&Parent, &FlowNodeOutput)>,
-    ) -> N::NodeIO {
-        let inputs = self.get_inputs(entity, q_nodes, q_inputs, q_outputs);
+#[derive(Component, Default, Deref, DerefMut)]
+pub struct FlowNode<N: FlowNodeSet>(pub(crate) N);
 
-        self.0.resolve(inputs, output)
-    }
-}
+/// The title shown on a node, changing it renames the node.
+#[derive(Clone, Component, Debug, Default, PartialEq, Eq)]
+pub struct FlowNodeTitle(pub String);
 
 #[derive(Resource)]
 pub struct FlowNodeConfig {
     pub border_thickness: f32,
-    pub color_border: Color,
-    pub color_node: Color,
-    pub color_title: Color,
     pub handle_size_io: f32,
     pub padding: f32,
     pub font_size_body: f32,
@@ -124,9 +133,6 @@ impl Default for FlowNodeConfig {
     fn default() -> Self {
         Self {
             border_thickness: 2.0,
-            color_border: Color::WHITE,
-            color_node: Color::rgb(0.3, 0.3, 0.3),
-            color_title: Color::rgb(0.004, 0.431, 0.49),
             handle_size_io: 6.0,
             padding: 5.0,
             font_size_body: 16.0,
@@ -135,10 +141,50 @@ impl Default for FlowNodeConfig {
     }
 }
 
+/// Data ports carry values pulled through [`FlowNodeSet::resolve`], execution ports sequence
+/// side effects through [`FlowNodeSet::execute`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum PortKind {
+    #[default]
+    Data,
+    Exec,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum PortShape {
+    #[default]
+    Circle,
+    Diamond,
+    Square,
+    /// Points in the direction of flow, intended for execution ports.
+    Triangle,
+}
+
+impl PortShape {
+    pub(crate) fn rotation(self) -> Quat {
+        match self {
+            Self::Triangle => Quat::from_rotation_z(-std::f32::consts::FRAC_PI_2),
+            _ => Quat::IDENTITY,
+        }
+    }
+}
+
+/// How a port is drawn, typically chosen per data type so users can tell what a port expects.
+///
+/// Ports without a colour use the theme's handle colours and draw their wires with the theme's
+/// connection colour.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct PortStyle {
+    pub color: Option<Color>,
+    pub shape: PortShape,
+}
+
 #[derive(Clone, Component, Default)]
 pub struct FlowNodeInput<N: FlowNodeSet> {
     pub connection: Option<Entity>,
+    pub kind: PortKind,
     pub label: String,
+    pub style: PortStyle,
     _phantom: PhantomData<N>,
 }
 
@@ -150,47 +196,93 @@ impl<N: FlowNodeSet> FlowNodeInput<N> {
         }
     }
 
-    pub fn get_input(
-        &self,
-        q_nodes: &Query<(Entity, &FlowNode<N>), Without<OutputNode>>,
-        q_inputs: &Query<(&Parent, &FlowNodeInput<N>)>,
-        q_outputs: &Query<(&Parent, &FlowNodeOutput)>,
-    ) -> Option<N::NodeIO> {
-        if let Some(connection) = self.connection {
-            if let Ok((parent, output)) = q_outputs.get(connection) {
-                if let Ok((entity, node)) = q_nodes.get(parent.get()) {
-                    return Some(node.resolve(
-                        entity,
-                        Some(&output.label),
-                        q_nodes,
-                        q_inputs,
-                        q_outputs,
-                    ));
-                }
-            }
+    pub fn exec(label: &str) -> Self {
+        Self {
+            kind: PortKind::Exec,
+            label: label.to_string(),
+            style: PortStyle {
+                shape: PortShape::Triangle,
+                ..default()
+            },
+            ..default()
         }
+    }
 
-        None
+    pub fn with_color(mut self, color: Color) -> Self {
+        self.style.color = Some(color);
+        self
+    }
+
+    pub fn with_shape(mut self, shape: PortShape) -> Self {
+        self.style.shape = shape;
+        self
     }
 }
 
-#[derive(Clone, Component)]
+#[derive(Clone, Component, Default)]
 pub struct FlowNodeOutput {
+    pub kind: PortKind,
     pub label: String,
+    pub style: PortStyle,
 }
 
 impl FlowNodeOutput {
     pub fn from_label(label: &str) -> Self {
         Self {
             label: label.to_string(),
+            ..default()
+        }
+    }
+
+    pub fn exec(label: &str) -> Self {
+        Self {
+            kind: PortKind::Exec,
+            label: label.to_string(),
+            style: PortStyle {
+                shape: PortShape::Triangle,
+                ..default()
+            },
         }
     }
+
+    pub fn with_color(mut self, color: Color) -> Self {
+        self.style.color = Some(color);
+        self
+    }
+
+    pub fn with_shape(mut self, shape: PortShape) -> Self {
+        self.style.shape = shape;
+        self
+    }
+}
+
+/// Materials swapped on an input handle as it is connected and disconnected.
+#[derive(Clone, Component)]
+pub(crate) struct InputMaterials {
+    pub active: Handle<ColorMaterial>,
+    pub inactive: Handle<ColorMaterial>,
 }
 
 #[derive(Event)]
 pub enum FlowNodeEvent<N: FlowNodeSet> {
-    Destroyed,
+    Deselected(Entity),
+    Destroyed(Entity),
+    Executed((Entity, Vec<(String, Option<N::NodeIO>)>)),
+    /// A node was dragged or laid out, positions are the centre of the node.
+    Moved {
+        entity: Entity,
+        from: Vec2,
+        to: Vec2,
+    },
+    Renamed {
+        entity: Entity,
+        from: String,
+        to: String,
+    },
     Resolved((Entity, N::NodeIO)),
+    Selected(Entity),
+    /// A node was built from its [`FlowNodeTemplate`].
+    Spawned(Entity),
 }
 
 #[derive(AsBindGroup, Asset, TypePath, TypeUuid, Debug, Clone, Default)]
@@ -223,56 +315,130 @@ pub(crate) struct FlowNodeResources {
     pub material_handle_input: Handle<ColorMaterial>,
     pub material_handle_input_inactive: Handle<ColorMaterial>,
     pub material_handle_output: Handle<ColorMaterial>,
-    pub mesh_handle_io: Handle<Mesh>,
+    pub mesh_handle_circle: Handle<Mesh>,
+    pub mesh_handle_diamond: Handle<Mesh>,
+    pub mesh_handle_square: Handle<Mesh>,
+    pub mesh_handle_triangle: Handle<Mesh>,
     pub text_style_body: TextStyle,
     pub text_style_title: TextStyle,
 }
 
+impl FlowNodeResources {
+    pub fn mesh_handle_io(&self, shape: PortShape) -> Handle<Mesh> {
+        match shape {
+            PortShape::Circle => self.mesh_handle_circle.clone(),
+            PortShape::Diamond => self.mesh_handle_diamond.clone(),
+            PortShape::Square => self.mesh_handle_square.clone(),
+            PortShape::Triangle => self.mesh_handle_triangle.clone(),
+        }
+    }
+
+    pub fn input_materials(
+        &self,
+        style: &PortStyle,
+        theme: &FlowNodeTheme,
+        materials: &mut Assets<ColorMaterial>,
+    ) -> InputMaterials {
+        if let Some(color) = style.color {
+            InputMaterials {
+                active: materials.add(color.into()),
+                inactive: materials.add(mix(color, theme.handle_input_inactive, 0.5).into()),
+            }
+        } else {
+            InputMaterials {
+                active: self.material_handle_input.clone(),
+                inactive: self.material_handle_input_inactive.clone(),
+            }
+        }
+    }
+
+    pub fn output_material(
+        &self,
+        style: &PortStyle,
+        materials: &mut Assets<ColorMaterial>,
+    ) -> Handle<ColorMaterial> {
+        if let Some(color) = style.color {
+            materials.add(color.into())
+        } else {
+            self.material_handle_output.clone()
+        }
+    }
+}
+
 #[derive(Component)]
 pub struct OutputNode;
 
+#[derive(Component)]
+pub(crate) enum FlowNodeText {
+    Body,
+    Title,
+}
+
 fn setup(
     mut commands: Commands,
     assets: Res<DefaultAssets>,
     config: Res<FlowNodeConfig>,
+    theme: Res<FlowNodeTheme>,
     mut materials: ResMut<Assets<ColorMaterial>>,
     mut meshes: ResMut<Assets<Mesh>>,
 ) {
-    commands.insert_resource(ActiveNode::default());
-
     let text_style_body = TextStyle {
         font: assets.font.clone(),
         font_size: config.font_size_body,
-        color: Color::WHITE,
+        color: theme.text_body,
     };
     let text_style_title = TextStyle {
         font: assets.font_bold.clone(),
         font_size: config.font_size_title,
-        color: Color::WHITE,
+        color: theme.text_title,
     };
 
     commands.insert_resource(FlowNodeResources {
-        material_handle_input: materials.add(Color::rgb(0.0, 0.992, 0.933).into()),
-        material_handle_input_inactive: materials.add(Color::rgb(0.541, 0.624, 0.62).into()),
-        material_handle_output: materials.add(Color::rgb(0.992, 0.475, 0.0).into()),
-        mesh_handle_io: meshes.add(shape::Circle::new(config.handle_size_io).into()),
+        material_handle_input: materials.add(theme.handle_input.into()),
+        material_handle_input_inactive: materials.add(theme.handle_input_inactive.into()),
+        material_handle_output: materials.add(theme.handle_output.into()),
+        mesh_handle_circle: meshes.add(shape::Circle::new(config.handle_size_io).into()),
+        mesh_handle_diamond: meshes
+            .add(shape::RegularPolygon::new(config.handle_size_io * 1.3, 4).into()),
+        mesh_handle_square: meshes
+            .add(shape::Quad::new(Vec2::splat(config.handle_size_io * 1.7)).into()),
+        mesh_handle_triangle: meshes
+            .add(shape::RegularPolygon::new(config.handle_size_io * 1.3, 3).into()),
         text_style_body,
         text_style_title,
     });
 }
 
 fn activate_node(
+    mut commands: Commands,
     cursor: Res<CursorPosition>,
-    mut active_node: ResMut<ActiveNode>,
-    mut materials: ResMut<Assets<FlowNodeMaterial>>,
     mut ev_click: EventReader<Clicked>,
-    mut q_node: Query<(&Handle<FlowNodeMaterial>, &mut Transform, &GlobalTransform)>,
+    mut q_editor: Query<&mut ActiveNode>,
+    mut q_node: Query<
+        (&mut Transform, &GlobalTransform, Has<Selected>),
+        With<Handle<FlowNodeMaterial>>,
+    >,
+    q_selected: Query<(Entity, &InEditor), With<Selected>>,
 ) {
-    for ev in ev_click.read() {
-        let to_deactivate = active_node.entity;
+    let Some(editor) = cursor.editor else {
+        ev_click.clear();
+        return;
+    };
+    let Ok(mut active_node) = q_editor.get_mut(editor) else {
+        ev_click.clear();
+        return;
+    };
+    let deselect_all = |commands: &mut Commands| {
+        for (entity, in_editor) in q_selected.iter() {
+            if in_editor.0 == editor {
+                commands.entity(entity).remove::<Selected>();
+            }
+        }
+    };
 
+    for ev in ev_click.read() {
         if let Clicked(Some(entity)) = ev {
-            if let Ok((handle, mut transform, global_transform)) = q_node.get_mut(*entity) {
+            if let Ok((mut transform, global_transform, selected)) = q_node.get_mut(*entity) {
                 active_node.offset = global_transform.translation().truncate() - cursor.position();
 
                 if let Some(active_entity) = active_node.entity {
@@ -286,97 +452,432 @@ fn activate_node(
                 active_node.index += 10.0;
                 active_node.index_reset = true;
 
-                let material = materials.get_mut(handle).unwrap();
-
-                material.active = 1;
+                // Clicking a node of the selection keeps the rest of it selected.
+                if !selected {
+                    deselect_all(&mut commands);
+                    commands.entity(*entity).insert(Selected);
+                }
             } else {
                 active_node.entity = None;
+                deselect_all(&mut commands);
             }
         } else {
             active_node.entity = None;
+            deselect_all(&mut commands);
         }
+    }
+}
 
-        if let Some(entity) = to_deactivate {
-            let (handle, _, _) = q_node.get(entity).unwrap();
-            let material = materials.get_mut(handle).unwrap();
-
+fn highlight_selected(
+    mut materials: ResMut<Assets<FlowNodeMaterial>>,
+    mut removed: RemovedComponents<Selected>,
+    q_added: Query<&Handle<FlowNodeMaterial>, Added<Selected>>,
+    q_nodes: Query<&Handle<FlowNodeMaterial>>,
+) {
+    for entity in removed.read() {
+        if let Some(material) = q_nodes.get(entity).ok().and_then(|h| materials.get_mut(h)) {
             material.active = 0;
         }
     }
+
+    for handle in q_added.iter() {
+        if let Some(material) = materials.get_mut(handle) {
+            material.active = 1;
+        }
+    }
+}
+
+fn toggle_selection(
+    mut commands: Commands,
+    bindings: Res<FlowNodeBindings>,
+    cursor: Res<CursorPosition>,
+    input: BindingInputs,
+    mut q_editor: Query<&mut ActiveNode>,
+    q_nodes: Query<
+        (
+            Entity,
+            &Clickable,
+            &GlobalTransform,
+            &InEditor,
+            Has<Selected>,
+        ),
+        With<Handle<FlowNodeMaterial>>,
+    >,
+) {
+    if !input.just_pressed(&bindings.extend_selection) {
+        return;
+    }
+
+    let Some(editor) = cursor.editor else {
+        return;
+    };
+    let picked = q_nodes
+        .iter()
+        .filter(|(_, clickable, transform, in_editor, _)| {
+            in_editor.0 == editor
+                && clickable.clicked(transform.translation().truncate(), cursor.position())
+        })
+        .max_by(|(_, _, a, _, _), (_, _, b, _, _)| a.translation().z.total_cmp(&b.translation().z));
+
+    if let Some((entity, _, _, _, selected)) = picked {
+        if selected {
+            commands.entity(entity).remove::<Selected>();
+
+            if let Ok(mut active_node) = q_editor.get_mut(editor) {
+                if active_node.entity == Some(entity) {
+                    active_node.entity = None;
+                }
+            }
+        } else {
+            commands.entity(entity).insert(Selected);
+        }
+    }
+}
+
+fn apply_theme<N: FlowNodeSet>(
+    theme: Res<FlowNodeTheme>,
+    mut node_res: ResMut<FlowNodeResources>,
+    mut color_materials: ResMut<Assets<ColorMaterial>>,
+    mut node_materials: ResMut<Assets<FlowNodeMaterial>>,
+    q_nodes: Query<(&FlowNode<N>, &Handle<FlowNodeMaterial>)>,
+    mut q_text: Query<(&FlowNodeText, &mut Text)>,
+) {
+    if !theme.is_changed() {
+        return;
+    }
+
+    node_res.text_style_body.color = theme.text_body;
+    node_res.text_style_title.color = theme.text_title;
+
+    for (handle, color) in [
+        (&node_res.material_handle_input, theme.handle_input),
+        (
+            &node_res.material_handle_input_inactive,
+            theme.handle_input_inactive,
+        ),
+        (&node_res.material_handle_output, theme.handle_output),
+    ] {
+        if let Some(material) = color_materials.get_mut(handle) {
+            material.color = color;
+        }
+    }
+
+    for (node, handle) in q_nodes.iter() {
+        if let Some(material) = node_materials.get_mut(handle) {
+            material.color = theme.node;
+            material.color_border = theme.node_border;
+            material.color_title = node.title_color(&theme);
+        }
+    }
+
+    for (node_text, mut text) in q_text.iter_mut() {
+        let color = match node_text {
+            FlowNodeText::Body => theme.text_body,
+            FlowNodeText::Title => theme.text_title,
+        };
+
+        for section in text.sections.iter_mut() {
+            section.style.color = color;
+        }
+    }
+}
+
+/// The nodes of `editor` acted on together, the active node followed by the rest of the selection.
+fn selection<N: FlowNodeSet>(
+    editor: Entity,
+    active_node: &ActiveNode,
+    q_selected: &Query<(Entity, &InEditor), (With<FlowNode<N>>, With<Selected>)>,
+) -> Vec<Entity> {
+    active_node
+        .entity
+        .into_iter()
+        .chain(
+            q_selected
+                .iter()
+                .filter(|(entity, in_editor)| {
+                    in_editor.0 == editor && active_node.entity != Some(*entity)
+                })
+                .map(|(entity, _)| entity),
+        )
+        .collect()
 }
 
+#[allow(clippy::too_many_arguments)]
 fn delete_node<N: FlowNodeSet>(
     mut commands: Commands,
-    mut active_node: ResMut<ActiveNode>,
-    keys: Res<Input<KeyCode>>,
-    node_res: Res<FlowNodeResources>,
+    bindings: Res<FlowNodeBindings>,
+    cursor: Res<CursorPosition>,
+    input: BindingInputs,
+    mut ev_connection: EventWriter<ConnectionEvent>,
     mut ev_node: EventWriter<FlowNodeEvent<N>>,
-    q_outputs: Query<(Entity, &Parent), With<FlowNodeOutput>>,
-    mut q_inputs: Query<(Entity, &mut FlowNodeInput<N>)>,
+    mut ev_rejected: EventWriter<ActionRejected>,
+    mut q_editor: Query<&mut ActiveNode>,
+    q_nodes: Query<(&FlowNode<N>, Has<Locked>)>,
+    q_selected: Query<(Entity, &InEditor), (With<FlowNode<N>>, With<Selected>)>,
+    q_outputs: Query<(Entity, &Parent, &FlowNodeOutput)>,
+    mut q_inputs: Query<(Entity, &Parent, &mut FlowNodeInput<N>, &InputMaterials)>,
     mut q_material: Query<(&Parent, &mut Handle<ColorMaterial>)>,
 ) {
-    if keys.just_pressed(KeyCode::Delete) {
-        if let Some(entity) = active_node.entity {
-            for (output_entity, _) in q_outputs
-                .iter()
-                .filter(|(_, parent)| parent.get() == entity)
-            {
-                for (input_entity, mut input) in q_inputs.iter_mut() {
-                    if let Some(output) = input.connection {
-                        if output == output_entity {
-                            input.connection = None;
-
-                            for (parent, mut material) in q_material.iter_mut() {
-                                if parent.get() == input_entity {
-                                    *material = node_res.material_handle_input_inactive.clone();
-                                }
-                            }
-                        }
-                    }
+    let Some(editor) = cursor.editor else {
+        return;
+    };
+    let Ok(mut active_node) = q_editor.get_mut(editor) else {
+        return;
+    };
+
+    if !input.just_pressed(&bindings.delete_node) {
+        return;
+    }
+
+    let deleted = selection(editor, &active_node, &q_selected)
+        .into_iter()
+        .filter(|entity| q_nodes.contains(*entity))
+        .collect::<Vec<_>>();
+
+    if deleted.is_empty() {
+        return;
+    }
+
+    // The selection is deleted as a whole or not at all.
+    for (node, locked) in deleted
+        .iter()
+        .filter_map(|entity| q_nodes.get(*entity).ok())
+    {
+        let allowed = if locked {
+            Err("This node is locked".to_string())
+        } else {
+            node.can_delete()
+        };
+
+        if let Err(reason) = allowed {
+            ev_rejected.send(ActionRejected { reason });
+            return;
+        }
+    }
+
+    for (input_entity, input_parent, mut input, input_materials) in q_inputs.iter_mut() {
+        let Some((output, output_parent, node_output)) = input
+            .connection
+            .and_then(|output| q_outputs.get(output).ok())
+        else {
+            continue;
+        };
+        let input_deleted = deleted.contains(&input_parent.get());
+
+        if !input_deleted && !deleted.contains(&output_parent.get()) {
+            continue;
+        }
+
+        ev_connection.send(ConnectionEvent::Destroyed(ConnectionInfo::new(
+            (input_entity, input_parent.get(), &*input),
+            (output, output_parent.get(), node_output),
+        )));
+
+        // Wires into a deleted node are despawned along with it.
+        if !input_deleted {
+            input.connection = None;
+
+            for (parent, mut material) in q_material.iter_mut() {
+                if parent.get() == input_entity {
+                    *material = input_materials.inactive.clone();
                 }
             }
-            commands.entity(entity).despawn_recursive();
-            active_node.count -= 1;
-            active_node.entity = None;
-            ev_node.send(FlowNodeEvent::Destroyed);
         }
     }
+
+    for entity in deleted {
+        commands.entity(entity).despawn_recursive();
+        active_node.count -= 1;
+        ev_node.send(FlowNodeEvent::Destroyed(entity));
+    }
+
+    active_node.entity = None;
 }
 
+// The node under the cursor and where every node dragged along with it started.
+struct NodeDrag {
+    entity: Entity,
+    from: Vec<(Entity, Vec2)>,
+    locked: bool,
+}
+
+#[allow(clippy::too_many_arguments)]
 fn drag_node<N: FlowNodeSet>(
-    active_node: Res<ActiveNode>,
+    bindings: Res<FlowNodeBindings>,
     cursor: Res<CursorPosition>,
-    mouse_button_input: Res<Input<MouseButton>>,
-    mut query: Query<&mut Transform, With<FlowNode<N>>>,
+    align: Option<Res<AlignConfig>>,
+    grid: Option<Res<GridConfig>>,
+    mut guides: Option<ResMut<AlignmentGuides>>,
+    input: BindingInputs,
+    mut ev_node: EventWriter<FlowNodeEvent<N>>,
+    mut ev_rejected: EventWriter<ActionRejected>,
+    mut drag: Local<Option<NodeDrag>>,
+    q_editor: Query<(&ActiveNode, &Camera, &OrthographicProjection)>,
+    q_nodes: Query<(Entity, &Clickable, &GlobalTransform, &InEditor), Without<Parent>>,
+    q_selected: Query<(Entity, &InEditor), (With<FlowNode<N>>, With<Selected>)>,
+    mut query: Query<(&mut Transform, &Clickable, Has<Locked>), With<FlowNode<N>>>,
 ) {
-    if mouse_button_input.just_pressed(MouseButton::Left)
-        || !mouse_button_input.pressed(MouseButton::Left)
-    {
+    if !input.pressed(&bindings.select) {
+        // A drag is reported once, when the nodes are dropped.
+        for (entity, from) in drag.take().into_iter().flat_map(|drag| drag.from) {
+            if let Ok((transform, _, _)) = query.get(entity) {
+                let to = transform.translation.truncate();
+
+                if to != from {
+                    ev_node.send(FlowNodeEvent::Moved { entity, from, to });
+                }
+            }
+        }
+
+        return;
+    }
+
+    if input.just_pressed(&bindings.select) {
+        return;
+    }
+
+    let Some(editor) = cursor.editor else {
+        return;
+    };
+    let Ok((active_node, camera, projection)) = q_editor.get(editor) else {
+        return;
+    };
+    let Some((entity, current, size)) = active_node.entity.and_then(|entity| {
+        let (transform, clickable, _) = query.get(entity).ok()?;
+        let size = match clickable {
+            Clickable::Area(size) => Some(*size),
+            _ => None,
+        };
+
+        Some((entity, transform.translation.truncate(), size))
+    }) else {
         return;
+    };
+    let mut position = cursor.position() + active_node.offset;
+
+    // Dragging a node of the selection drags the whole selection, started once the cursor
+    // actually moves.
+    if drag.as_ref().map_or(true, |drag| drag.entity != entity) {
+        if position == current {
+            return;
+        }
+
+        let from = selection(editor, active_node, &q_selected)
+            .into_iter()
+            .filter_map(|entity| {
+                let (transform, _, _) = query.get(entity).ok()?;
+
+                Some((entity, transform.translation.truncate()))
+            })
+            .collect::<Vec<_>>();
+        let locked = from
+            .iter()
+            .any(|(entity, _)| query.get(*entity).is_ok_and(|(_, _, locked)| locked));
+
+        if locked {
+            ev_rejected.send(ActionRejected {
+                reason: "This node is locked".to_string(),
+            });
+        }
+
+        *drag = Some(NodeDrag {
+            entity,
+            from,
+            locked,
+        });
+    }
+
+    let Some(drag) = drag.as_ref().filter(|drag| !drag.locked) else {
+        return;
+    };
+    let snap = !input.pressed(&bindings.disable_snap);
+
+    if let (Some(grid), Some(size)) = (&grid, size) {
+        if grid.snap && snap {
+            position = snap_to_grid(position, size, grid.size);
+        }
+    }
+
+    if let (Some(align), Some(guides), Some(size)) = (&align, &mut guides, size) {
+        if align.guides && snap {
+            let pixel = world_per_pixel(camera, projection).x;
+            let others = q_nodes
+                .iter()
+                .filter(|(other, _, _, in_editor)| {
+                    in_editor.0 == editor && drag.from.iter().all(|(dragged, _)| dragged != other)
+                })
+                .filter_map(|(_, clickable, transform, _)| match clickable {
+                    Clickable::Area(size) => Some(Rect::from_center_size(
+                        transform.translation().truncate(),
+                        *size,
+                    )),
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+
+            position = guides.snap(
+                editor,
+                position,
+                size,
+                &others,
+                align.guide_threshold * pixel,
+                pixel,
+            );
+        }
+    }
+
+    let delta = position - current;
+
+    for (entity, _) in drag.from.iter() {
+        if let Ok((mut transform, _, _)) = query.get_mut(*entity) {
+            transform.translation.x += delta.x;
+            transform.translation.y += delta.y;
+        }
     }
+}
 
-    if let Some(entity) = active_node.entity {
-        if let Ok(mut transform) = query.get_mut(entity) {
-            transform.translation.x = cursor.x + active_node.offset.x;
-            transform.translation.y = cursor.y + active_node.offset.y;
+fn rename_nodes<N: FlowNodeSet>(
+    mut ev_node: EventWriter<FlowNodeEvent<N>>,
+    q_nodes: Query<
+        (Entity, &FlowNodeTitle, &Children),
+        (With<FlowNode<N>>, Changed<FlowNodeTitle>),
+    >,
+    mut q_text: Query<(&FlowNodeText, &mut Text)>,
+) {
+    for (entity, title, children) in q_nodes.iter() {
+        for child in children.iter() {
+            let Ok((FlowNodeText::Title, mut text)) = q_text.get_mut(*child) else {
+                continue;
+            };
+
+            // Titles are also changed when the node is built.
+            if text.sections[0].value != title.0 {
+                let from = std::mem::replace(&mut text.sections[0].value, title.0.clone());
+
+                ev_node.send(FlowNodeEvent::Renamed {
+                    entity,
+                    from,
+                    to: title.0.clone(),
+                });
+            }
         }
     }
 }
 
-fn resolve_output_nodes<N: FlowNodeSet>(
-    mut ev_resolution: EventWriter<FlowNodeEvent<N>>,
-    mut ev_connection: EventReader<ConnectionEvent>,
-    q_output: Query<(Entity, &FlowNode<N>), With<OutputNode>>,
-    q_nodes: Query<(Entity, &FlowNode<N>), Without<OutputNode>>,
-    q_inputs: Query<(&Parent, &FlowNodeInput<N>)>,
-    q_outputs: Query<(&Parent, &FlowNodeOutput)>,
+fn report_selection<N: FlowNodeSet>(
+    mut ev_node: EventWriter<FlowNodeEvent<N>>,
+    mut removed: RemovedComponents<Selected>,
+    q_added: Query<Entity, (With<FlowNode<N>>, Added<Selected>)>,
+    q_nodes: Query<(), (With<FlowNode<N>>, Without<Selected>)>,
 ) {
-    if ev_connection.read().next().is_some() {
-        for (entity, node) in q_output.iter() {
-            ev_resolution.send(FlowNodeEvent::Resolved((
-                entity,
-                node.resolve(entity, None, &q_nodes, &q_inputs, &q_outputs),
-            )));
+    for entity in removed.read() {
+        // Deleted nodes are reported as destroyed instead.
+        if q_nodes.contains(entity) {
+            ev_node.send(FlowNodeEvent::Deselected(entity));
         }
     }
+
+    for entity in q_added.iter() {
+        ev_node.send(FlowNodeEvent::Selected(entity));
+    }
 }
@@ -0,0 +1,85 @@
+use bevy::ecs::system::ReadOnlySystemParam;
+use std::collections::HashMap;
+
+use crate::evaluation::{EvalContext, NodeInputs};
+#[cfg(feature = "editor")]
+use crate::{template::FlowNodeTemplate, theme::FlowNodeTheme};
+#[cfg(feature = "editor")]
+use bevy::prelude::Color;
+
+/// The nodes of a graph and how values flow through them, shared by the editor and the
+/// [`FlowNodeRuntimePlugin`](crate::runtime::FlowNodeRuntimePlugin).
+///
+/// Editing nodes requires the `editor` feature, which `template` and `title_color` belong to.
+pub trait FlowNodeSet: 'static + Clone + Default + Sized + Send + Sync {
+    type NodeIO: Clone + Send + Sync;
+    /// Kept by every node between evaluations, for accumulators, counters, delays and the like.
+    /// A node with several outputs wired steps once per evaluation, every output is resolved
+    /// from the same state and only the changes made resolving the first one are kept.
+    type State: Clone + Default + Send + Sync;
+    /// Read-only system parameters handed to nodes through [`EvalContext::params`], `()` when
+    /// nodes do not read the world.
+    type Context: ReadOnlySystemParam + 'static;
+
+    fn resolve(
+        &self,
+        context: &EvalContext<Self>,
+        state: &mut Self::State,
+        inputs: &NodeInputs<Self>,
+        output: Option<&str>,
+    ) -> Self::NodeIO;
+    /// Resolves every output of the node at once, keyed by label, for nodes whose outputs share
+    /// an expensive computation. The values are computed once per evaluation and `resolve` is
+    /// only called for output nodes when this returns `Some`.
+    fn resolve_outputs(
+        &self,
+        _context: &EvalContext<Self>,
+        _state: &mut Self::State,
+        _inputs: &NodeInputs<Self>,
+    ) -> Option<HashMap<String, Self::NodeIO>> {
+        None
+    }
+    #[cfg(feature = "editor")]
+    fn template(self) -> FlowNodeTemplate<Self>;
+    /// Runs the node when execution reaches it through an execution port, its data inputs
+    /// are resolved beforehand.
+    fn execute(
+        &self,
+        _context: &EvalContext<Self>,
+        _state: &mut Self::State,
+        _inputs: &NodeInputs<Self>,
+    ) -> ExecFlow {
+        ExecFlow::Continue
+    }
+    /// The state of a node when it is spawned or reset.
+    fn initial_state(&self) -> Self::State {
+        Self::State::default()
+    }
+    #[cfg(feature = "editor")]
+    fn title_color(&self, theme: &FlowNodeTheme) -> Color {
+        theme.node_title
+    }
+    /// Whether the user may wire the output labelled `output` of this node to the input
+    /// labelled `input` of `to`, the error is shown next to the cursor.
+    fn can_connect(&self, _output: &str, _to: &Self, _input: &str) -> Result<(), String> {
+        Ok(())
+    }
+    fn can_delete(&self) -> Result<(), String> {
+        Ok(())
+    }
+    /// Whether the user may unplug the wire from the output labelled `output` of this node to
+    /// the input labelled `input` of `to`.
+    fn can_disconnect(&self, _output: &str, _to: &Self, _input: &str) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+/// Which execution outputs to continue through after [`FlowNodeSet::execute`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ExecFlow {
+    /// Continue through every execution output, top to bottom.
+    Continue,
+    /// Continue through the execution output with this label only.
+    Output(String),
+    Stop,
+}
@@ -0,0 +1,127 @@
+use bevy::prelude::*;
+
+use crate::{
+    assets::DefaultAssets,
+    cursor::CursorPosition,
+    editor::EditorSet,
+    theme::FlowNodeTheme,
+};
+
+// Keeps notices clear of the cursor.
+const NOTICE_OFFSET: Vec2 = Vec2::new(16.0, 16.0);
+
+/// Shows why an action was rejected next to the cursor.
+pub struct NoticePlugin;
+
+impl Plugin for NoticePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<FlowNodeTheme>()
+            .init_resource::<NoticeConfig>()
+            .add_event::<ActionRejected>()
+            .add_systems(
+                Update,
+                (fade_notices, show_notices.before(fade_notices)).in_set(EditorSet::Render),
+            );
+    }
+}
+
+#[derive(Resource)]
+pub struct NoticeConfig {
+    /// Seconds a notice stays on screen, it fades out during the last third.
+    pub duration: f32,
+    pub font_size: f32,
+}
+
+impl Default for NoticeConfig {
+    fn default() -> Self {
+        Self {
+            duration: 2.0,
+            font_size: 16.0,
+        }
+    }
+}
+
+/// An action of the user was vetoed by a [`FlowNodeSet`](crate::node_set::FlowNodeSet) hook,
+/// or could not be done at all, like wiring an execution port to a data port.
+#[derive(Clone, Debug, Event)]
+pub struct ActionRejected {
+    pub reason: String,
+}
+
+#[derive(Component)]
+struct Notice(Timer);
+
+fn fade_notices(
+    mut commands: Commands,
+    theme: Res<FlowNodeTheme>,
+    time: Res<Time>,
+    mut q_notices: Query<(Entity, &mut Notice, &mut BackgroundColor, &Children)>,
+    mut q_text: Query<&mut Text>,
+) {
+    for (entity, mut notice, mut background, children) in q_notices.iter_mut() {
+        if notice.0.tick(time.delta()).finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+
+        let alpha = (notice.0.percent_left() * 3.0).min(1.0);
+
+        background.0 = theme.notice.with_a(theme.notice.a() * alpha);
+
+        for child in children.iter() {
+            if let Ok(mut text) = q_text.get_mut(*child) {
+                for section in text.sections.iter_mut() {
+                    section.style.color = theme.notice_text.with_a(theme.notice_text.a() * alpha);
+                }
+            }
+        }
+    }
+}
+
+fn show_notices(
+    mut commands: Commands,
+    assets: Res<DefaultAssets>,
+    config: Res<NoticeConfig>,
+    cursor: Res<CursorPosition>,
+    theme: Res<FlowNodeTheme>,
+    mut ev_rejected: EventReader<ActionRejected>,
+    q_notices: Query<Entity, With<Notice>>,
+) {
+    let Some(ev) = ev_rejected.read().last() else {
+        return;
+    };
+
+    // Only the latest reason is shown.
+    for entity in q_notices.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    let position = Vec2::new(cursor.screen_x, cursor.screen_y) + NOTICE_OFFSET;
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    left: Val::Px(position.x.min(cursor.editor_rect.max.x)),
+                    top: Val::Px(position.y.min(cursor.editor_rect.max.y)),
+                    padding: UiRect::all(Val::Px(5.0)),
+                    ..default()
+                },
+                background_color: theme.notice.into(),
+                z_index: ZIndex::Global(i32::MAX),
+                ..default()
+            },
+            Notice(Timer::from_seconds(config.duration, TimerMode::Once)),
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                ev.reason.clone(),
+                TextStyle {
+                    font: assets.font.clone(),
+                    font_size: config.font_size,
+                    color: theme.notice_text,
+                },
+            ));
+        });
+}
@@ -0,0 +1,575 @@
+#![allow(clippy::type_complexity)]
+
+use bevy::{
+    ecs::system::{StaticSystemParam, SystemParamItem},
+    prelude::*,
+};
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    fmt,
+    marker::PhantomData,
+    mem,
+};
+
+use crate::{
+    evaluation::{
+        EvalContext,
+        EvaluateNodes,
+        EvaluationClock,
+        EvaluationMode,
+        InputSource,
+        NodeInputs,
+    },
+    node_set::FlowNodeSet,
+};
+
+/// Resolves every [`RuntimeGraph`] of `N` following the [`EvaluationMode`], without any of
+/// the editor.
+pub struct FlowNodeRuntimePlugin<N: FlowNodeSet>(PhantomData<N>);
+
+impl<N: FlowNodeSet> Default for FlowNodeRuntimePlugin<N> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<N: FlowNodeSet> Plugin for FlowNodeRuntimePlugin<N> {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<EvaluationMode>()
+            .add_event::<EvaluateNodes>()
+            .add_systems(Update, evaluate_graphs::<N>.in_set(RuntimeSet));
+    }
+}
+
+/// Where runtime graphs are resolved, set their inputs before it and read their outputs after
+/// it.
+#[derive(SystemSet, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct RuntimeSet;
+
+/// A graph saved from an editor, see [`FlowNodeGraph::save`](crate::graph::FlowNodeGraph::save).
+///
+/// Only data wires are saved, graphs driven by execution wires stay in the editor.
+#[derive(Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound(
+        serialize = "N: serde::Serialize, N::State: serde::Serialize",
+        deserialize = "N: serde::Deserialize<'de>, N::State: serde::Deserialize<'de>"
+    ))
+)]
+pub struct SavedGraph<N: FlowNodeSet> {
+    pub connections: Vec<SavedConnection>,
+    pub nodes: Vec<SavedNode<N>>,
+}
+
+/// A wire from the output labelled `output` of the node at index `from` to the input labelled
+/// `input` of the node at index `to`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SavedConnection {
+    pub from: usize,
+    pub input: String,
+    pub output: String,
+    pub to: usize,
+}
+
+#[derive(Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound(
+        serialize = "N: serde::Serialize, N::State: serde::Serialize",
+        deserialize = "N: serde::Deserialize<'de>, N::State: serde::Deserialize<'de>"
+    ))
+)]
+pub struct SavedNode<N: FlowNodeSet> {
+    /// The labels of the data inputs, top to bottom.
+    pub inputs: Vec<String>,
+    pub node: N,
+    /// Whether the node is an output node, resolved on its own.
+    pub output: bool,
+    /// The labels of the data outputs, top to bottom.
+    pub outputs: Vec<String>,
+    pub state: N::State,
+    pub title: String,
+}
+
+/// A [`SavedGraph`] ready to be resolved, gameplay systems set its inputs and read its output
+/// nodes around [`RuntimeSet`].
+#[derive(Component)]
+pub struct RuntimeGraph<N: FlowNodeSet> {
+    changed: bool,
+    nodes: Vec<RuntimeNode<N>>,
+}
+
+struct RuntimeNode<N: FlowNodeSet> {
+    inputs: Vec<RuntimeInput<N>>,
+    node: N,
+    outputs: Vec<String>,
+    state: N::State,
+    title: String,
+    // The last value of an output node.
+    value: Option<Option<N::NodeIO>>,
+}
+
+struct RuntimeInput<N: FlowNodeSet> {
+    label: String,
+    // The node and the output wired into the input.
+    source: Option<(usize, usize)>,
+    value: Option<N::NodeIO>,
+}
+
+impl<N: FlowNodeSet> RuntimeGraph<N> {
+    pub fn load(graph: &SavedGraph<N>) -> Self {
+        let mut nodes = graph
+            .nodes
+            .iter()
+            .map(|saved| RuntimeNode {
+                inputs: saved
+                    .inputs
+                    .iter()
+                    .map(|label| RuntimeInput {
+                        label: label.clone(),
+                        source: None,
+                        value: None,
+                    })
+                    .collect(),
+                node: saved.node.clone(),
+                outputs: saved.outputs.clone(),
+                state: saved.state.clone(),
+                title: saved.title.clone(),
+                value: saved.output.then_some(None),
+            })
+            .collect::<Vec<_>>();
+
+        for connection in graph.connections.iter() {
+            let output = nodes.get(connection.from).and_then(|from| {
+                from.outputs
+                    .iter()
+                    .position(|label| *label == connection.output)
+            });
+            let input = nodes.get_mut(connection.to).and_then(|to| {
+                to.inputs
+                    .iter_mut()
+                    .find(|input| input.label == connection.input)
+            });
+
+            match (input, output) {
+                (Some(input), Some(output)) => input.source = Some((connection.from, output)),
+                (None, _) => warn!("Node has no input labelled {:?}", connection.input),
+                (_, None) => warn!("Node has no output labelled {:?}", connection.output),
+            }
+        }
+
+        Self {
+            changed: true,
+            nodes,
+        }
+    }
+
+    /// The index in [`SavedGraph::nodes`] of the only node titled `title`.
+    pub fn node(&self, title: &str) -> Result<usize, FindNodeError> {
+        let mut nodes = self
+            .nodes
+            .iter()
+            .enumerate()
+            .filter(|(_, runtime)| runtime.title == title)
+            .map(|(index, _)| index);
+
+        match (nodes.next(), nodes.next()) {
+            (Some(index), None) => Ok(index),
+            (Some(_), Some(_)) => Err(FindNodeError::Ambiguous(title.to_string())),
+            (None, _) => Err(FindNodeError::Missing(title.to_string())),
+        }
+    }
+
+    /// The value of the output node at index `node` when the graph was last resolved, `None`
+    /// until then.
+    pub fn output(&self, node: usize) -> Option<&N::NodeIO> {
+        self.nodes
+            .get(node)
+            .and_then(|runtime| runtime.value.as_ref()?.as_ref())
+    }
+
+    /// Puts every node back in its [`FlowNodeSet::initial_state`].
+    pub fn reset(&mut self) {
+        for runtime in self.nodes.iter_mut() {
+            runtime.state = runtime.node.initial_state();
+        }
+
+        self.changed = true;
+    }
+
+    /// Resolves every output node of the graph.
+    pub fn resolve(&mut self, entity: Entity, params: &SystemParamItem<N::Context>, time: &Time) {
+        let states = self
+            .nodes
+            .iter_mut()
+            .map(|runtime| RefCell::new(mem::take(&mut runtime.state)))
+            .collect();
+        let evaluation = RuntimeEvaluation {
+            entity,
+            nodes: &self.nodes,
+            outputs: RefCell::default(),
+            params,
+            resolving: RefCell::default(),
+            states,
+            stepped: RefCell::default(),
+            time,
+            values: RefCell::default(),
+        };
+        let values = (0..self.nodes.len())
+            .filter(|&index| self.nodes[index].value.is_some())
+            .map(|index| (index, evaluation.resolve(index, None)))
+            .collect::<Vec<_>>();
+
+        let states = evaluation.states;
+
+        for (runtime, state) in self.nodes.iter_mut().zip(states) {
+            runtime.state = state.into_inner();
+        }
+
+        for (index, value) in values {
+            self.nodes[index].value = Some(value);
+        }
+
+        self.changed = false;
+    }
+
+    /// Sets the value of the input labelled `input` of the node at index `node`, used while
+    /// nothing is wired into it. Returns `false` when there is no such input.
+    pub fn set_input(&mut self, node: usize, input: &str, value: N::NodeIO) -> bool {
+        let input = self.nodes.get_mut(node).and_then(|runtime| {
+            runtime
+                .inputs
+                .iter_mut()
+                .find(|runtime_input| runtime_input.label == input)
+        });
+
+        let Some(input) = input else {
+            return false;
+        };
+
+        input.value = Some(value);
+        self.changed = true;
+
+        true
+    }
+}
+
+/// No single node of a [`RuntimeGraph`] has the title passed to [`RuntimeGraph::node`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FindNodeError {
+    /// Several nodes share the title, look them up by index instead.
+    Ambiguous(String),
+    Missing(String),
+}
+
+impl fmt::Display for FindNodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FindNodeError::Ambiguous(title) => write!(f, "several nodes are titled \"{title}\""),
+            FindNodeError::Missing(title) => write!(f, "no node is titled \"{title}\""),
+        }
+    }
+}
+
+impl std::error::Error for FindNodeError {}
+
+// A single pass over a runtime graph, see `Evaluation` for the editor.
+struct RuntimeEvaluation<'a, 'w, 's, N: FlowNodeSet> {
+    entity: Entity,
+    nodes: &'a [RuntimeNode<N>],
+    outputs: RefCell<HashMap<usize, HashMap<String, N::NodeIO>>>,
+    params: &'a SystemParamItem<'w, 's, N::Context>,
+    resolving: RefCell<HashSet<usize>>,
+    states: Vec<RefCell<N::State>>,
+    stepped: RefCell<HashMap<usize, N::State>>,
+    time: &'a Time,
+    values: RefCell<HashMap<usize, Vec<(Option<String>, Option<N::NodeIO>)>>>,
+}
+
+impl<'a, 'w, 's, N: FlowNodeSet> RuntimeEvaluation<'a, 'w, 's, N> {
+    fn resolve(&self, index: usize, output: Option<&str>) -> Option<N::NodeIO> {
+        let resolved = self.values.borrow().get(&index).and_then(|values| {
+            values
+                .iter()
+                .find(|(label, _)| label.as_deref() == output)
+                .map(|(_, value)| value.clone())
+        });
+
+        if let Some(value) = resolved {
+            return value;
+        }
+
+        if let (Some(output), Some(outputs)) = (output, self.outputs.borrow().get(&index)) {
+            return outputs.get(output).cloned();
+        }
+
+        // A node reached again through a cycle of wires reads as disconnected.
+        if !self.resolving.borrow_mut().insert(index) {
+            return None;
+        }
+
+        let value = self.resolve_node(index, output);
+
+        self.resolving.borrow_mut().remove(&index);
+        self.values
+            .borrow_mut()
+            .entry(index)
+            .or_default()
+            .push((output.map(str::to_string), value.clone()));
+
+        value
+    }
+
+    fn resolve_node(&self, index: usize, output: Option<&str>) -> Option<N::NodeIO> {
+        let runtime = self.nodes.get(index)?;
+        let ports = RuntimePorts {
+            evaluation: self,
+            node: runtime,
+        };
+        let inputs = NodeInputs::new(&ports);
+        let context = EvalContext {
+            entity: self.entity,
+            params: self.params,
+            time: self.time,
+            title: &runtime.title,
+        };
+        let mut state = self.states[index].borrow_mut();
+        let mut before = self.stepped.borrow().get(&index).cloned();
+
+        if before.is_none() {
+            self.stepped.borrow_mut().insert(index, state.clone());
+        }
+
+        let state = before.as_mut().unwrap_or(&mut state);
+
+        if let Some(output) = output {
+            if let Some(outputs) = runtime.node.resolve_outputs(&context, state, &inputs) {
+                let value = outputs.get(output).cloned();
+
+                self.outputs.borrow_mut().insert(index, outputs);
+
+                return value;
+            }
+        }
+
+        Some(runtime.node.resolve(&context, state, &inputs, output))
+    }
+}
+
+struct RuntimePorts<'a, 'w, 's, N: FlowNodeSet> {
+    evaluation: &'a RuntimeEvaluation<'a, 'w, 's, N>,
+    node: &'a RuntimeNode<N>,
+}
+
+impl<'a, 'w, 's, N: FlowNodeSet> InputSource<N> for RuntimePorts<'a, 'w, 's, N> {
+    fn label(&self, index: usize) -> Option<&str> {
+        self.node
+            .inputs
+            .get(index)
+            .map(|input| input.label.as_str())
+    }
+
+    fn len(&self) -> usize {
+        self.node.inputs.len()
+    }
+
+    fn value(&self, index: usize) -> Option<N::NodeIO> {
+        let input = self.node.inputs.get(index)?;
+
+        match input.source {
+            Some((node, output)) => {
+                let label = self.evaluation.nodes.get(node)?.outputs.get(output)?;
+
+                self.evaluation.resolve(node, Some(label))
+            }
+            None => input.value.clone(),
+        }
+    }
+}
+
+fn evaluate_graphs<N: FlowNodeSet>(
+    params: StaticSystemParam<N::Context>,
+    mode: Res<EvaluationMode>,
+    time: Res<Time>,
+    mut clock: Local<EvaluationClock>,
+    mut ev_evaluate: EventReader<EvaluateNodes>,
+    mut q_graphs: Query<(Entity, &mut RuntimeGraph<N>)>,
+) {
+    let requested = ev_evaluate.read().count() > 0;
+    let times = clock.tick(*mode, &time);
+
+    for (entity, mut graph) in q_graphs.iter_mut() {
+        let changed = graph.changed && *mode == EvaluationMode::OnChange;
+
+        if times.is_empty() && (changed || requested) {
+            graph.resolve(entity, &params, &clock.now(*mode, &time));
+        }
+
+        for time in times.iter() {
+            graph.resolve(entity, &params, time);
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    #[cfg(feature = "editor")]
+    use crate::template::FlowNodeTemplate;
+
+    /// A counter, an adder and a node passing its input on, with `i32` values.
+    #[derive(Clone, Default)]
+    pub(crate) enum TestNodes {
+        #[default]
+        Counter,
+        Add,
+        Pass,
+    }
+
+    impl FlowNodeSet for TestNodes {
+        type NodeIO = i32;
+        type State = i32;
+        type Context = ();
+
+        fn resolve(
+            &self,
+            _context: &EvalContext<Self>,
+            state: &mut Self::State,
+            inputs: &NodeInputs<Self>,
+            output: Option<&str>,
+        ) -> Self::NodeIO {
+            match self {
+                TestNodes::Counter => {
+                    *state += 1;
+
+                    match output {
+                        Some("double") => *state * 2,
+                        _ => *state,
+                    }
+                }
+                TestNodes::Add => inputs.get_or("a", 0) + inputs.get_or("b", 0),
+                TestNodes::Pass => inputs.get_or("value", -1),
+            }
+        }
+
+        #[cfg(feature = "editor")]
+        fn template(self) -> FlowNodeTemplate<Self> {
+            FlowNodeTemplate {
+                node: self,
+                ..default()
+            }
+        }
+    }
+
+    fn node(node: TestNodes, title: &str, inputs: &[&str], output: bool) -> SavedNode<TestNodes> {
+        SavedNode {
+            inputs: inputs.iter().map(|label| label.to_string()).collect(),
+            node,
+            output,
+            outputs: vec!["value".to_string()],
+            state: 0,
+            title: title.to_string(),
+        }
+    }
+
+    fn wire(from: usize, to: usize, input: &str) -> SavedConnection {
+        SavedConnection {
+            from,
+            input: input.to_string(),
+            output: "value".to_string(),
+            to,
+        }
+    }
+
+    fn resolve(graph: &mut RuntimeGraph<TestNodes>) {
+        graph.resolve(Entity::PLACEHOLDER, &(), &Time::default());
+    }
+
+    #[test]
+    fn stateful_node_steps_once_per_pass() {
+        let mut graph = RuntimeGraph::load(&SavedGraph {
+            connections: vec![wire(0, 1, "a"), wire(0, 1, "b")],
+            nodes: vec![
+                node(TestNodes::Counter, "Counter", &[], false),
+                node(TestNodes::Add, "Sum", &["a", "b"], true),
+            ],
+        });
+
+        resolve(&mut graph);
+        assert_eq!(graph.output(1), Some(&2));
+
+        resolve(&mut graph);
+        assert_eq!(graph.output(1), Some(&4));
+    }
+
+    #[test]
+    fn stateful_node_steps_once_for_every_output() {
+        let mut counter = node(TestNodes::Counter, "Counter", &[], false);
+
+        counter.outputs.push("double".to_string());
+
+        let mut graph = RuntimeGraph::load(&SavedGraph {
+            connections: vec![
+                wire(0, 1, "a"),
+                SavedConnection {
+                    output: "double".to_string(),
+                    ..wire(0, 1, "b")
+                },
+            ],
+            nodes: vec![counter, node(TestNodes::Add, "Sum", &["a", "b"], true)],
+        });
+
+        resolve(&mut graph);
+        assert_eq!(graph.output(1), Some(&3));
+
+        resolve(&mut graph);
+        assert_eq!(graph.output(1), Some(&6));
+    }
+
+    #[test]
+    fn cycle_reads_as_disconnected() {
+        let mut graph = RuntimeGraph::load(&SavedGraph {
+            connections: vec![wire(0, 1, "value"), wire(1, 0, "value")],
+            nodes: vec![
+                node(TestNodes::Pass, "First", &["value"], true),
+                node(TestNodes::Pass, "Second", &["value"], false),
+            ],
+        });
+
+        resolve(&mut graph);
+        assert_eq!(graph.output(0), Some(&-1));
+    }
+
+    #[test]
+    fn titles_find_a_single_node() {
+        let mut graph = RuntimeGraph::load(&SavedGraph {
+            connections: vec![],
+            nodes: vec![
+                node(TestNodes::Pass, "Pass", &["value"], true),
+                node(TestNodes::Pass, "Pass", &["value"], true),
+                node(TestNodes::Pass, "Last", &["value"], true),
+            ],
+        });
+
+        assert_eq!(graph.node("Last"), Ok(2));
+        assert_eq!(
+            graph.node("Pass"),
+            Err(FindNodeError::Ambiguous("Pass".to_string()))
+        );
+        assert_eq!(
+            graph.node("First"),
+            Err(FindNodeError::Missing("First".to_string()))
+        );
+
+        assert!(graph.set_input(1, "value", 7));
+        assert!(!graph.set_input(1, "other", 7));
+        resolve(&mut graph);
+        assert_eq!(graph.output(0), Some(&-1));
+        assert_eq!(graph.output(1), Some(&7));
+    }
+}
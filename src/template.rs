@@ -6,18 +6,28 @@ use bevy::{
 use std::marker::PhantomData;
 
 use crate::{
+    cursor::{CursorCamera, CursorPosition},
+    editor::{EditorSet, InEditor},
+    execution::EventNode,
+    graph::FlowNodeState,
+    grid::{snap_to_grid, GridConfig},
     interactions::Clickable,
     node::{
         ActiveNode,
         FlowNode,
         FlowNodeConfig,
+        FlowNodeEvent,
         FlowNodeInput,
         FlowNodeMaterial,
         FlowNodeOutput,
         FlowNodeResources,
-        FlowNodeSet,
+        FlowNodeText,
+        FlowNodeTitle,
+        Locked,
         OutputNode,
     },
+    node_set::FlowNodeSet,
+    theme::FlowNodeTheme,
 };
 
 #[derive(Default)]
@@ -27,7 +37,7 @@ pub struct FlowNodeTemplatePlugin<N: FlowNodeSet> {
 
 impl<N: FlowNodeSet> Plugin for FlowNodeTemplatePlugin<N> {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, build_node::<N>);
+        app.add_systems(Update, build_node::<N>.in_set(EditorSet::Layout));
     }
 }
 
@@ -48,11 +58,20 @@ impl FlowNodeSlot {
 
 #[derive(Component)]
 pub struct FlowNodeTemplate<N: FlowNodeSet> {
+    /// The editor to spawn the node into, defaults to the editor under the cursor.
+    pub editor: Option<Entity>,
+    /// Makes the node an entry point for [`ExecTrigger`](crate::execution::ExecTrigger)s with
+    /// this name.
+    pub event: Option<String>,
     pub inputs: Option<Vec<FlowNodeInput<N>>>,
+    /// Spawns the node [`Locked`].
+    pub locked: bool,
     pub node: N,
     pub outputs: Option<Vec<FlowNodeOutput>>,
     pub position: Vec2,
     pub slot: Option<FlowNodeSlot>,
+    /// Restores a saved state, defaults to [`FlowNodeSet::initial_state`].
+    pub state: Option<N::State>,
     pub title: String,
     pub width: f32,
 }
@@ -60,27 +79,46 @@ pub struct FlowNodeTemplate<N: FlowNodeSet> {
 impl<N: FlowNodeSet> Default for FlowNodeTemplate<N> {
     fn default() -> Self {
         Self {
+            editor: None,
+            event: None,
             inputs: None,
+            locked: false,
             node: N::default(),
             position: Vec2::ZERO,
             outputs: None,
             slot: None,
+            state: None,
             title: "Node".to_string(),
             width: 200.0,
         }
     }
 }
 
-fn build_node<N: FlowNodeSet>(
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn build_node<N: FlowNodeSet>(
     mut commands: Commands,
     config: Res<FlowNodeConfig>,
     resources: Res<FlowNodeResources>,
-    mut active_node: ResMut<ActiveNode>,
+    theme: Res<FlowNodeTheme>,
+    cursor: Res<CursorPosition>,
+    grid: Option<Res<GridConfig>>,
+    mut color_materials: ResMut<Assets<ColorMaterial>>,
     mut materials: ResMut<Assets<FlowNodeMaterial>>,
     mut meshes: ResMut<Assets<Mesh>>,
+    mut ev_node: EventWriter<FlowNodeEvent<N>>,
+    mut q_editor: Query<(Entity, &mut ActiveNode), With<CursorCamera>>,
     query: Query<(Entity, &FlowNodeTemplate<N>)>,
 ) {
     for (entity, template) in query.iter() {
+        // Templates wait until their editor has been initialised.
+        let editor = template
+            .editor
+            .or(cursor.editor)
+            .or_else(|| q_editor.iter().next().map(|(editor, _)| editor));
+        let Some((editor, mut active_node)) = editor.and_then(|e| q_editor.get_mut(e).ok()) else {
+            continue;
+        };
+        let in_editor = InEditor(editor);
         let n_inputs = if let Some(inputs) = &template.inputs {
             inputs.len()
         } else {
@@ -102,6 +140,10 @@ fn build_node<N: FlowNodeSet>(
         let height_title = config.font_size_title + config.padding * 2.0;
         let height = height_body + height_title + 2.0;
         let node_size = Vec2::new(template.width, height);
+        let position = match &grid {
+            Some(grid) if grid.snap => snap_to_grid(template.position, node_size, grid.size),
+            _ => template.position,
+        };
         let width_interior = template.width - 2.0 * config.padding;
         let bounds_title = Vec2::new(width_interior, config.font_size_title);
         let bounds_io = Vec2::new(width_interior, config.font_size_body);
@@ -114,9 +156,9 @@ fn build_node<N: FlowNodeSet>(
             .insert((
                 MaterialMesh2dBundle {
                     material: materials.add(FlowNodeMaterial {
-                        color: config.color_node,
-                        color_border: config.color_border,
-                        color_title: config.color_title,
+                        color: theme.node,
+                        color_border: theme.node_border,
+                        color_title: template.node.title_color(&theme),
                         size: node_size,
                         border_thickness: config.border_thickness,
                         height_title,
@@ -131,14 +173,12 @@ fn build_node<N: FlowNodeSet>(
                             .into(),
                         ),
                     ),
-                    transform: Transform::from_xyz(
-                        template.position.x,
-                        template.position.y,
-                        active_node.index,
-                    ),
+                    transform: Transform::from_xyz(position.x, position.y, active_node.index),
                     ..default()
                 },
                 Clickable::Area(node_size),
+                FlowNodeTitle(template.title.clone()),
+                in_editor,
             ))
             .with_children(|parent| {
                 parent.spawn(SpatialBundle {
@@ -146,13 +186,19 @@ fn build_node<N: FlowNodeSet>(
                     ..default()
                 });
 
-                parent.spawn(Text2dBundle {
-                    text: Text::from_section(&template.title, resources.text_style_title.clone()),
-                    text_anchor: Anchor::TopLeft,
-                    text_2d_bounds: Text2dBounds { size: bounds_title },
-                    transform: Transform::from_xyz(offset_x, offset_y, 2.0),
-                    ..default()
-                });
+                parent.spawn((
+                    Text2dBundle {
+                        text: Text::from_section(
+                            &template.title,
+                            resources.text_style_title.clone(),
+                        ),
+                        text_anchor: Anchor::TopLeft,
+                        text_2d_bounds: Text2dBounds { size: bounds_title },
+                        transform: Transform::from_xyz(offset_x, offset_y, 2.0),
+                        ..default()
+                    },
+                    FlowNodeText::Title,
+                ));
 
                 offset_y -= height_title;
 
@@ -160,34 +206,40 @@ fn build_node<N: FlowNodeSet>(
                     for output in outputs {
                         parent.spawn((
                             MaterialMesh2dBundle {
-                                material: resources.material_handle_output.clone(),
-                                mesh: Mesh2dHandle(resources.mesh_handle_io.clone()),
+                                material: resources
+                                    .output_material(&output.style, &mut color_materials),
+                                mesh: Mesh2dHandle(resources.mesh_handle_io(output.style.shape)),
                                 transform: Transform::from_xyz(
                                     node_size.x / 2.0,
                                     offset_y - config.handle_size_io - config.padding,
                                     2.0,
-                                ),
+                                )
+                                .with_rotation(output.style.shape.rotation()),
                                 ..default()
                             },
                             (*output).clone(),
                             Clickable::Radius(config.handle_size_io),
+                            in_editor,
                         ));
 
-                        parent.spawn(Text2dBundle {
-                            text: Text::from_section(
-                                output.label.clone(),
-                                resources.text_style_body.clone(),
-                            )
-                            .with_alignment(TextAlignment::Right),
-                            text_anchor: Anchor::TopRight,
-                            text_2d_bounds: Text2dBounds { size: bounds_io },
-                            transform: Transform::from_xyz(
-                                node_size.x / 2.0 - config.handle_size_io - config.padding,
-                                offset_y - config.font_size_body + config.handle_size_io * 2.0,
-                                1.0,
-                            ),
-                            ..default()
-                        });
+                        parent.spawn((
+                            Text2dBundle {
+                                text: Text::from_section(
+                                    output.label.clone(),
+                                    resources.text_style_body.clone(),
+                                )
+                                .with_alignment(TextAlignment::Right),
+                                text_anchor: Anchor::TopRight,
+                                text_2d_bounds: Text2dBounds { size: bounds_io },
+                                transform: Transform::from_xyz(
+                                    node_size.x / 2.0 - config.handle_size_io - config.padding,
+                                    offset_y - config.font_size_body + config.handle_size_io * 2.0,
+                                    1.0,
+                                ),
+                                ..default()
+                            },
+                            FlowNodeText::Body,
+                        ));
 
                         offset_y -= height_io;
                     }
@@ -197,6 +249,9 @@ fn build_node<N: FlowNodeSet>(
 
                 if let Some(inputs) = &template.inputs {
                     for input in inputs.iter() {
+                        let input_materials =
+                            resources.input_materials(&input.style, &theme, &mut color_materials);
+
                         parent
                             .spawn((
                                 SpatialBundle {
@@ -208,30 +263,38 @@ fn build_node<N: FlowNodeSet>(
                                     ..default()
                                 },
                                 (*input).clone(),
+                                input_materials.clone(),
                                 Clickable::Radius(config.handle_size_io),
+                                in_editor,
                             ))
                             .with_children(|parent| {
                                 parent.spawn(MaterialMesh2dBundle {
-                                    material: resources.material_handle_input_inactive.clone(),
-                                    mesh: Mesh2dHandle(resources.mesh_handle_io.clone()),
+                                    material: input_materials.inactive,
+                                    mesh: Mesh2dHandle(resources.mesh_handle_io(input.style.shape)),
+                                    transform: Transform::from_rotation(
+                                        input.style.shape.rotation(),
+                                    ),
                                     ..default()
                                 });
                             });
 
-                        parent.spawn(Text2dBundle {
-                            text: Text::from_section(
-                                input.label.clone(),
-                                resources.text_style_body.clone(),
-                            ),
-                            text_anchor: Anchor::TopLeft,
-                            text_2d_bounds: Text2dBounds { size: bounds_io },
-                            transform: Transform::from_xyz(
-                                offset_x + config.padding,
-                                offset_y - config.font_size_body + config.handle_size_io * 2.0,
-                                1.0,
-                            ),
-                            ..default()
-                        });
+                        parent.spawn((
+                            Text2dBundle {
+                                text: Text::from_section(
+                                    input.label.clone(),
+                                    resources.text_style_body.clone(),
+                                ),
+                                text_anchor: Anchor::TopLeft,
+                                text_2d_bounds: Text2dBounds { size: bounds_io },
+                                transform: Transform::from_xyz(
+                                    offset_x + config.padding,
+                                    offset_y - config.font_size_body + config.handle_size_io * 2.0,
+                                    1.0,
+                                ),
+                                ..default()
+                            },
+                            FlowNodeText::Body,
+                        ));
 
                         offset_y -= height_io;
                     }
@@ -247,17 +310,35 @@ fn build_node<N: FlowNodeSet>(
                             ..default()
                         },
                         slot,
+                        in_editor,
                     ));
                 }
             })
-            .insert(FlowNode(template.node.clone()))
+            .insert((
+                FlowNode(template.node.clone()),
+                FlowNodeState::<N>::new(
+                    template
+                        .state
+                        .clone()
+                        .unwrap_or_else(|| template.node.initial_state()),
+                ),
+            ))
             .remove::<FlowNodeTemplate<N>>();
 
         if output {
             commands.entity(entity).insert(OutputNode);
         }
 
+        if template.locked {
+            commands.entity(entity).insert(Locked);
+        }
+
+        if let Some(event) = &template.event {
+            commands.entity(entity).insert(EventNode(event.clone()));
+        }
+
         active_node.count += 1;
         active_node.index += 10.0;
+        ev_node.send(FlowNodeEvent::Spawned(entity));
     }
 }
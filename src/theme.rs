@@ -0,0 +1,146 @@
+use bevy::prelude::*;
+
+/// Colours used by every part of the editor.
+///
+/// Changing the resource re-themes nodes, wires, menus and widgets that have already been
+/// spawned. Per-node title colours can be overridden with [`FlowNodeSet::title_color`].
+///
+/// [`FlowNodeSet::title_color`]: crate::node_set::FlowNodeSet::title_color
+#[derive(Clone, Debug, Resource)]
+pub struct FlowNodeTheme {
+    /// Clears the editor cameras with this colour, their own clear colour is left alone when
+    /// `None`.
+    pub background: Option<Color>,
+    pub connection: Color,
+    pub grid_major: Color,
+    pub grid_minor: Color,
+    pub handle_input: Color,
+    pub handle_input_inactive: Color,
+    pub handle_output: Color,
+    pub input_background: Color,
+    pub input_cursor: Color,
+    pub input_selection: Color,
+    pub input_text: Color,
+    pub menu: Color,
+    pub menu_hover: Color,
+    pub menu_text: Color,
+    pub minimap: Color,
+    pub minimap_node: Color,
+    pub minimap_view: Color,
+    pub node: Color,
+    pub node_border: Color,
+    pub node_title: Color,
+    pub notice: Color,
+    pub notice_text: Color,
+    pub text_body: Color,
+    pub text_title: Color,
+    pub widget_text: Color,
+}
+
+impl Default for FlowNodeTheme {
+    fn default() -> Self {
+        Self::dark()
+    }
+}
+
+impl FlowNodeTheme {
+    pub fn dark() -> Self {
+        Self {
+            background: None,
+            connection: Color::WHITE,
+            grid_major: Color::rgba(1.0, 1.0, 1.0, 0.12),
+            grid_minor: Color::rgba(1.0, 1.0, 1.0, 0.05),
+            handle_input: Color::rgb(0.0, 0.992, 0.933),
+            handle_input_inactive: Color::rgb(0.541, 0.624, 0.62),
+            handle_output: Color::rgb(0.992, 0.475, 0.0),
+            input_background: Color::WHITE,
+            input_cursor: Color::BLACK,
+            input_selection: Color::rgb(0.6, 0.8, 1.0),
+            input_text: Color::BLACK,
+            menu: Color::rgb(0.1, 0.1, 0.1),
+            menu_hover: Color::rgb(0.3, 0.3, 0.3),
+            menu_text: Color::WHITE,
+            minimap: Color::rgba(0.1, 0.1, 0.1, 0.8),
+            minimap_node: Color::rgb(0.4, 0.4, 0.4),
+            minimap_view: Color::WHITE,
+            node: Color::rgb(0.3, 0.3, 0.3),
+            node_border: Color::WHITE,
+            node_title: Color::rgb(0.004, 0.431, 0.49),
+            notice: Color::rgb(0.45, 0.1, 0.1),
+            notice_text: Color::WHITE,
+            text_body: Color::WHITE,
+            text_title: Color::WHITE,
+            widget_text: Color::WHITE,
+        }
+    }
+
+    pub fn light() -> Self {
+        Self {
+            background: None,
+            connection: Color::rgb(0.25, 0.25, 0.25),
+            grid_major: Color::rgba(0.0, 0.0, 0.0, 0.15),
+            grid_minor: Color::rgba(0.0, 0.0, 0.0, 0.06),
+            handle_input: Color::rgb(0.0, 0.6, 0.56),
+            handle_input_inactive: Color::rgb(0.65, 0.65, 0.65),
+            handle_output: Color::rgb(0.9, 0.4, 0.0),
+            input_background: Color::rgb(0.93, 0.93, 0.93),
+            input_cursor: Color::BLACK,
+            input_selection: Color::rgb(0.6, 0.8, 1.0),
+            input_text: Color::BLACK,
+            menu: Color::rgb(0.97, 0.97, 0.97),
+            menu_hover: Color::rgb(0.82, 0.82, 0.82),
+            menu_text: Color::rgb(0.1, 0.1, 0.1),
+            minimap: Color::rgba(0.97, 0.97, 0.97, 0.8),
+            minimap_node: Color::rgb(0.7, 0.7, 0.7),
+            minimap_view: Color::rgb(0.2, 0.2, 0.2),
+            node: Color::rgb(0.99, 0.99, 0.99),
+            node_border: Color::rgb(0.2, 0.2, 0.2),
+            node_title: Color::rgb(0.31, 0.55, 0.8),
+            notice: Color::rgb(0.95, 0.8, 0.8),
+            notice_text: Color::rgb(0.45, 0.05, 0.05),
+            text_body: Color::rgb(0.1, 0.1, 0.1),
+            text_title: Color::WHITE,
+            widget_text: Color::rgb(0.1, 0.1, 0.1),
+        }
+    }
+
+    pub fn high_contrast() -> Self {
+        Self {
+            background: None,
+            connection: Color::WHITE,
+            grid_major: Color::rgb(0.35, 0.35, 0.35),
+            grid_minor: Color::rgb(0.18, 0.18, 0.18),
+            handle_input: Color::CYAN,
+            handle_input_inactive: Color::GRAY,
+            handle_output: Color::YELLOW,
+            input_background: Color::WHITE,
+            input_cursor: Color::BLACK,
+            input_selection: Color::YELLOW,
+            input_text: Color::BLACK,
+            menu: Color::BLACK,
+            menu_hover: Color::rgb(0.0, 0.0, 0.55),
+            menu_text: Color::WHITE,
+            minimap: Color::BLACK,
+            minimap_node: Color::GRAY,
+            minimap_view: Color::YELLOW,
+            node: Color::rgb(0.12, 0.12, 0.12),
+            node_border: Color::YELLOW,
+            node_title: Color::rgb(0.0, 0.0, 0.55),
+            notice: Color::RED,
+            notice_text: Color::WHITE,
+            text_body: Color::WHITE,
+            text_title: Color::WHITE,
+            widget_text: Color::YELLOW,
+        }
+    }
+}
+
+/// Linearly blends `a` towards `b` in sRGB space.
+pub(crate) fn mix(a: Color, b: Color, t: f32) -> Color {
+    Color::rgba(
+        a.r() + (b.r() - a.r()) * t,
+        a.g() + (b.g() - a.g()) * t,
+        a.b() + (b.b() - a.b()) * t,
+        a.a() + (b.a() - a.a()) * t,
+    )
+}
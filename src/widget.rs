@@ -3,9 +3,13 @@ use std::marker::PhantomData;
 
 use crate::{
     assets::DefaultAssets,
+    editor::EditorSet,
+    focus::EditorFocus,
     interactions::{Clickable, Clicked},
-    node::{FlowNode, FlowNodeSet},
+    node::FlowNode,
+    node_set::FlowNodeSet,
     template::FlowNodeSlot,
+    theme::FlowNodeTheme,
 };
 
 pub trait Widget: Clone + Component {
@@ -18,6 +22,7 @@ pub trait Widget: Clone + Component {
         commands: &mut Commands,
         area: Vec2,
         assets: &Res<DefaultAssets>,
+        theme: &FlowNodeTheme,
     );
     fn can_click(&self) -> bool {
         false
@@ -37,13 +42,14 @@ pub struct WidgetPlugin<N: FlowNodeSet + SlotWidget<N, W>, W: Widget>(PhantomDat
 
 impl<N: FlowNodeSet + SlotWidget<N, W>, W: Widget> Plugin for WidgetPlugin<N, W> {
     fn build(&self, app: &mut App) {
-        app.insert_resource(ActiveWidget::<N, W>::default())
+        app.init_resource::<EditorFocus>()
+            .init_resource::<FlowNodeTheme>()
+            .insert_resource(ActiveWidget::<N, W>::default())
             .add_systems(
                 Update,
                 (
-                    focus_blur_widget::<N, W>,
-                    build_widget::<W>,
-                    slot_widget::<N, W>,
+                    focus_blur_widget::<N, W>.in_set(EditorSet::Interaction),
+                    (build_widget::<W>, slot_widget::<N, W>).in_set(EditorSet::Layout),
                 ),
             );
     }
@@ -66,6 +72,7 @@ impl<N: FlowNodeSet, W: Widget> Default for ActiveWidget<N, W> {
 
 fn focus_blur_widget<N: FlowNodeSet, W: Widget>(
     mut active_widget: ResMut<ActiveWidget<N, W>>,
+    mut focus: ResMut<EditorFocus>,
     mut ev_click: EventReader<Clicked>,
     mut query: Query<(Entity, &mut W), With<Clickable>>,
 ) {
@@ -73,8 +80,17 @@ fn focus_blur_widget<N: FlowNodeSet, W: Widget>(
         let mut needs_blur = false;
 
         if let Clicked(Some(entity)) = ev {
-            if let Ok((_, mut widget)) = query.get_mut(*entity) {
+            if query.contains(*entity) {
+                if let Some(previous) = active_widget.entity.filter(|e| e != entity) {
+                    if let Ok((_, mut widget)) = query.get_mut(previous) {
+                        widget.blur();
+                    }
+                }
+
+                let (_, mut widget) = query.get_mut(*entity).unwrap();
+
                 active_widget.entity = Some(*entity);
+                *focus = EditorFocus::Widget(*entity);
                 widget.focus();
             } else {
                 needs_blur = true;
@@ -83,8 +99,18 @@ fn focus_blur_widget<N: FlowNodeSet, W: Widget>(
             needs_blur = true;
         }
 
-        if needs_blur && active_widget.entity.is_some() {
-            if let Ok((_, mut widget)) = query.get_mut(active_widget.entity.unwrap()) {
+        if needs_blur {
+            if let Some(entity) = active_widget.entity {
+                if *focus == EditorFocus::Widget(entity) {
+                    *focus = EditorFocus::Canvas;
+                }
+            }
+        }
+    }
+
+    if let Some(entity) = active_widget.entity {
+        if *focus != EditorFocus::Widget(entity) {
+            if let Ok((_, mut widget)) = query.get_mut(entity) {
                 widget.blur();
             }
             active_widget.entity = None;
@@ -95,6 +121,7 @@ fn focus_blur_widget<N: FlowNodeSet, W: Widget>(
 fn build_widget<W: Widget>(
     mut commands: Commands,
     assets: Res<DefaultAssets>,
+    theme: Res<FlowNodeTheme>,
     mut q_widget: Query<(Entity, &mut W, &FlowNodeSlot)>,
 ) {
     for (entity, mut widget, slot) in q_widget.iter_mut() {
@@ -103,6 +130,7 @@ fn build_widget<W: Widget>(
             &mut commands,
             Vec2::new(slot.width, slot.height),
             &assets,
+            &theme,
         );
 
         commands.entity(entity).remove::<FlowNodeSlot>();
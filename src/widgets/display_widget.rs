@@ -7,7 +7,10 @@ use std::{fmt::Display, marker::PhantomData};
 
 use crate::{
     assets::DefaultAssets,
-    node::{FlowNodeEvent, FlowNodeSet},
+    editor::EditorSet,
+    node::FlowNodeEvent,
+    node_set::FlowNodeSet,
+    theme::FlowNodeTheme,
     widget::{SlotWidget, Widget, WidgetPlugin},
 };
 
@@ -20,7 +23,10 @@ where
 {
     fn build(&self, app: &mut App) {
         app.add_plugins(WidgetPlugin::<N, DisplayWidget>::default())
-            .add_systems(Update, update_display_widget::<N>);
+            .add_systems(
+                Update,
+                (apply_display_widget_theme, update_display_widget::<N>).in_set(EditorSet::Render),
+            );
     }
 }
 
@@ -39,11 +45,12 @@ impl Widget for DisplayWidget {
         commands: &mut Commands,
         area: Vec2,
         assets: &Res<DefaultAssets>,
+        theme: &FlowNodeTheme,
     ) {
         let text_style_title = TextStyle {
             font: assets.font.clone(),
             font_size: 16.0,
-            color: Color::WHITE,
+            color: theme.widget_text,
         };
 
         self.size = area;
@@ -68,6 +75,19 @@ impl Widget for DisplayWidget {
     }
 }
 
+fn apply_display_widget_theme(
+    theme: Res<FlowNodeTheme>,
+    mut q_text: Query<&mut Text, With<DisplayWidget>>,
+) {
+    if theme.is_changed() {
+        for mut text in q_text.iter_mut() {
+            for section in text.sections.iter_mut() {
+                section.style.color = theme.widget_text;
+            }
+        }
+    }
+}
+
 fn update_display_widget<N: FlowNodeSet>(
     mut ev_node: EventReader<FlowNodeEvent<N>>,
     mut q_text: Query<(&DisplayWidget, &mut Text)>,
@@ -1,26 +1,66 @@
-use bevy::{prelude::*, sprite::Anchor, text::Text2dBounds};
-use std::{fmt::Display, marker::PhantomData};
+use bevy::{
+    input::{keyboard::KeyboardInput, ButtonState},
+    prelude::*,
+    sprite::Anchor,
+    text::Text2dBounds,
+};
+use std::{fmt::Display, marker::PhantomData, ops::Range};
 
 use crate::{
     assets::DefaultAssets,
     connection::ConnectionEvent,
-    node::{FlowNode, FlowNodeSet},
+    editor::{editor_editable, EditorSet},
+    focus::EditorFocus,
+    node::FlowNode,
+    node_set::FlowNodeSet,
+    theme::FlowNodeTheme,
     widget::{SlotWidget, Widget, WidgetPlugin},
 };
 
+const FONT_SIZE: f32 = 16.0;
+// Advance of a single glyph of the monospaced default font.
+const CHAR_WIDTH: f32 = FONT_SIZE * 0.6;
+
+/// A value edited through an [`InputWidget`], indices are counted in characters.
 pub trait InputWidgetValue {
-    fn pop(&mut self);
-    fn push(&mut self, c: char);
+    /// Inserts `c` before the character at `index`, returning `false` if it was rejected.
+    fn insert(&mut self, index: usize, c: char) -> bool;
+    fn remove(&mut self, range: Range<usize>);
     fn to_string(&self) -> String;
-}
 
-impl InputWidgetValue for String {
     fn pop(&mut self) {
-        self.pop();
+        let len = self.to_string().chars().count();
+
+        if len > 0 {
+            self.remove(len - 1..len);
+        }
     }
 
     fn push(&mut self, c: char) {
-        self.push(c);
+        let len = self.to_string().chars().count();
+
+        self.insert(len, c);
+    }
+}
+
+fn byte_index(s: &str, index: usize) -> usize {
+    s.char_indices().nth(index).map_or(s.len(), |(i, _)| i)
+}
+
+impl InputWidgetValue for String {
+    fn insert(&mut self, index: usize, c: char) -> bool {
+        let index = byte_index(self, index);
+
+        self.insert(index, c);
+
+        true
+    }
+
+    fn remove(&mut self, range: Range<usize>) {
+        let start = byte_index(self, range.start);
+        let end = byte_index(self, range.end);
+
+        self.replace_range(start..end, "");
     }
 
     fn to_string(&self) -> String {
@@ -55,27 +95,27 @@ impl From<NumberInput> for String {
     }
 }
 
+impl NumberInput {
+    fn parse(&mut self) {
+        self.value = self.s_value.parse().unwrap_or(0.0);
+    }
+}
+
 impl InputWidgetValue for NumberInput {
-    fn pop(&mut self) {
-        self.s_value.pop();
+    fn insert(&mut self, index: usize, c: char) -> bool {
+        if c.is_ascii_digit() || (c == '.' && !self.s_value.contains('.')) {
+            InputWidgetValue::insert(&mut self.s_value, index, c);
+            self.parse();
 
-        if let Ok(value) = self.s_value.parse() {
-            self.value = value;
+            true
         } else {
-            self.value = 0.0;
+            false
         }
     }
 
-    fn push(&mut self, c: char) {
-        if c.is_ascii_digit() {
-            self.s_value.push(c);
-
-            if let Ok(value) = self.s_value.parse::<f32>() {
-                self.value = value;
-            }
-        } else if c == '.' && !self.s_value.chars().any(|c| c == '.') {
-            self.s_value.push(c);
-        }
+    fn remove(&mut self, range: Range<usize>) {
+        InputWidgetValue::remove(&mut self.s_value, range);
+        self.parse();
     }
 
     fn to_string(&self) -> String {
@@ -95,7 +135,15 @@ where
         app.add_plugins(WidgetPlugin::<N, InputWidget<V>>::default())
             .add_systems(
                 Update,
-                (input_widget_input::<V>, input_widget_value::<N, V>),
+                (
+                    apply_input_widget_theme::<V>.in_set(EditorSet::Render),
+                    (
+                        input_widget_cursor::<V>.after(input_widget_input::<V>),
+                        input_widget_input::<V>.run_if(editor_editable),
+                    )
+                        .in_set(EditorSet::Interaction),
+                    input_widget_value::<N, V>.in_set(EditorSet::Evaluate),
+                ),
             );
     }
 }
@@ -103,10 +151,83 @@ where
 #[derive(Clone, Component, Default)]
 pub struct InputWidget<V: InputWidgetValue> {
     pub active: bool,
+    pub background_entity: Option<Entity>,
+    pub cursor: usize,
+    pub cursor_entity: Option<Entity>,
     pub dirty: bool,
+    pub selection: Option<usize>,
+    pub selection_entity: Option<Entity>,
     pub size: Vec2,
     pub text_entity: Option<Entity>,
     pub value: V,
+    original: Option<V>,
+}
+
+impl<V: InputWidgetValue> InputWidget<V> {
+    fn len(&self) -> usize {
+        self.value.to_string().chars().count()
+    }
+
+    /// The selected characters, if any, between the selection anchor and the cursor.
+    pub fn selection_range(&self) -> Option<Range<usize>> {
+        self.selection
+            .filter(|anchor| *anchor != self.cursor)
+            .map(|anchor| anchor.min(self.cursor)..anchor.max(self.cursor))
+    }
+
+    pub fn move_cursor(&mut self, cursor: usize, extend_selection: bool) {
+        if extend_selection {
+            self.selection.get_or_insert(self.cursor);
+        } else {
+            self.selection = None;
+        }
+
+        self.cursor = cursor.min(self.len());
+    }
+
+    pub fn select_all(&mut self) {
+        self.selection = Some(0);
+        self.cursor = self.len();
+    }
+
+    fn delete_selection(&mut self) -> bool {
+        if let Some(range) = self.selection_range() {
+            self.cursor = range.start;
+            self.selection = None;
+            self.value.remove(range);
+            self.dirty = true;
+
+            true
+        } else {
+            self.selection = None;
+
+            false
+        }
+    }
+
+    pub fn insert(&mut self, c: char) {
+        self.delete_selection();
+
+        if self.value.insert(self.cursor, c) {
+            self.cursor += 1;
+            self.dirty = true;
+        }
+    }
+
+    pub fn delete_backward(&mut self) {
+        if !self.delete_selection() && self.cursor > 0 {
+            self.cursor -= 1;
+            self.value.remove(self.cursor..self.cursor + 1);
+            self.dirty = true;
+        }
+    }
+
+    pub fn delete_forward(&mut self) {
+        if !self.delete_selection() && self.cursor < self.len() {
+            self.value.remove(self.cursor..self.cursor + 1);
+            self.dirty = true;
+        }
+    }
 }
 
 impl<V: InputWidgetValue + 'static + Clone + Send + Sync> Widget for InputWidget<V> {
@@ -117,11 +238,12 @@ impl<V: InputWidgetValue + 'static + Clone + Send + Sync> Widget for InputWidget
         commands: &mut Commands,
         area: Vec2,
         assets: &Res<DefaultAssets>,
+        theme: &FlowNodeTheme,
     ) {
         let text_style_title = TextStyle {
             font: assets.font.clone(),
-            font_size: 16.0,
-            color: Color::BLACK,
+            font_size: FONT_SIZE,
+            color: theme.input_text,
         };
 
         self.size = area;
@@ -129,7 +251,7 @@ impl<V: InputWidgetValue + 'static + Clone + Send + Sync> Widget for InputWidget
         let child = commands
             .spawn(SpriteBundle {
                 sprite: Sprite {
-                    color: Color::WHITE,
+                    color: theme.input_background,
                     custom_size: Some(self.size),
                     ..default()
                 },
@@ -146,15 +268,44 @@ impl<V: InputWidgetValue + 'static + Clone + Send + Sync> Widget for InputWidget
                     })
                     .id();
 
+                let selection_entity = parent
+                    .spawn(SpriteBundle {
+                        sprite: Sprite {
+                            color: theme.input_selection,
+                            anchor: Anchor::TopLeft,
+                            ..default()
+                        },
+                        visibility: Visibility::Hidden,
+                        ..default()
+                    })
+                    .id();
+                let cursor_entity = parent
+                    .spawn(SpriteBundle {
+                        sprite: Sprite {
+                            color: theme.input_cursor,
+                            custom_size: Some(Vec2::new(1.5, FONT_SIZE)),
+                            anchor: Anchor::TopCenter,
+                            ..default()
+                        },
+                        visibility: Visibility::Hidden,
+                        ..default()
+                    })
+                    .id();
+
                 self.text_entity = Some(text_entity);
+                self.selection_entity = Some(selection_entity);
+                self.cursor_entity = Some(cursor_entity);
             })
             .id();
 
+        self.background_entity = Some(child);
         commands.entity(entity).push_children(&[child]);
     }
 
     fn blur(&mut self) {
         self.active = false;
+        self.original = None;
+        self.selection = None;
     }
 
     fn can_click(&self) -> bool {
@@ -163,6 +314,9 @@ impl<V: InputWidgetValue + 'static + Clone + Send + Sync> Widget for InputWidget
 
     fn focus(&mut self) {
         self.active = true;
+        self.cursor = self.len();
+        self.original = Some(self.value.clone());
+        self.selection = None;
     }
 
     fn size(&self) -> Vec2 {
@@ -170,21 +324,128 @@ impl<V: InputWidgetValue + 'static + Clone + Send + Sync> Widget for InputWidget
     }
 }
 
-fn input_widget_input<V: InputWidgetValue + 'static + Send + Sync>(
+fn apply_input_widget_theme<V: InputWidgetValue + 'static + Send + Sync>(
+    theme: Res<FlowNodeTheme>,
+    q_widget: Query<&InputWidget<V>>,
+    mut q_sprite: Query<&mut Sprite>,
+    mut q_text: Query<&mut Text>,
+) {
+    if !theme.is_changed() {
+        return;
+    }
+
+    for widget in q_widget.iter() {
+        for (entity, color) in [
+            (widget.background_entity, theme.input_background),
+            (widget.cursor_entity, theme.input_cursor),
+            (widget.selection_entity, theme.input_selection),
+        ] {
+            if let Some(mut sprite) = entity.and_then(|e| q_sprite.get_mut(e).ok()) {
+                sprite.color = color;
+            }
+        }
+
+        if let Some(mut text) = widget.text_entity.and_then(|e| q_text.get_mut(e).ok()) {
+            for section in text.sections.iter_mut() {
+                section.style.color = theme.input_text;
+            }
+        }
+    }
+}
+
+fn input_widget_input<V: InputWidgetValue + 'static + Clone + Send + Sync>(
+    mut focus: ResMut<EditorFocus>,
+    keys: Res<Input<KeyCode>>,
     mut ev_char: EventReader<ReceivedCharacter>,
+    mut ev_key: EventReader<KeyboardInput>,
     mut query: Query<&mut InputWidget<V>>,
 ) {
-    const BACKSPACE: char = '\u{0008}';
+    let EditorFocus::Widget(entity) = *focus else {
+        ev_char.clear();
+        ev_key.clear();
+        return;
+    };
+    let Ok(mut widget) = query.get_mut(entity) else {
+        ev_char.clear();
+        ev_key.clear();
+        return;
+    };
+    let control = keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]);
+    let shift = keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+
+    for ev in ev_key.read() {
+        if ev.state != ButtonState::Pressed {
+            continue;
+        }
+
+        match ev.key_code {
+            Some(KeyCode::Left) => {
+                let cursor = widget.cursor.saturating_sub(1);
+
+                widget.move_cursor(cursor, shift);
+            }
+            Some(KeyCode::Right) => {
+                let cursor = widget.cursor + 1;
+
+                widget.move_cursor(cursor, shift);
+            }
+            Some(KeyCode::Home) => widget.move_cursor(0, shift),
+            Some(KeyCode::End) => widget.move_cursor(usize::MAX, shift),
+            Some(KeyCode::A) if control => widget.select_all(),
+            Some(KeyCode::Back) => widget.delete_backward(),
+            Some(KeyCode::Delete) => widget.delete_forward(),
+            Some(KeyCode::Return | KeyCode::NumpadEnter) => {
+                widget.original = None;
+                *focus = EditorFocus::Canvas;
+            }
+            Some(KeyCode::Escape) => {
+                if let Some(original) = widget.original.take() {
+                    widget.value = original;
+                    widget.dirty = true;
+                }
+
+                *focus = EditorFocus::Canvas;
+            }
+            _ => {}
+        }
+    }
 
     for ev in ev_char.read() {
-        for mut widget in query.iter_mut() {
-            if widget.active {
-                widget.dirty = true;
-
-                if ev.char.is_ascii_graphic() {
-                    widget.value.push(ev.char);
-                } else if ev.char == BACKSPACE {
-                    widget.value.pop();
+        if *focus == EditorFocus::Widget(entity) && !ev.char.is_control() && !control {
+            widget.insert(ev.char);
+        }
+    }
+}
+
+fn input_widget_cursor<V: InputWidgetValue + 'static + Send + Sync>(
+    q_widget: Query<&InputWidget<V>, Changed<InputWidget<V>>>,
+    mut q_sprite: Query<(&mut Sprite, &mut Transform, &mut Visibility)>,
+) {
+    for widget in q_widget.iter() {
+        let origin = Vec2::new(-widget.size.x / 2.0, widget.size.y / 2.0);
+
+        if let Some(entity) = widget.cursor_entity {
+            if let Ok((_, mut transform, mut visibility)) = q_sprite.get_mut(entity) {
+                transform.translation =
+                    (origin + Vec2::X * widget.cursor as f32 * CHAR_WIDTH).extend(3.0);
+                *visibility = if widget.active {
+                    Visibility::Inherited
+                } else {
+                    Visibility::Hidden
+                };
+            }
+        }
+
+        if let Some(entity) = widget.selection_entity {
+            if let Ok((mut sprite, mut transform, mut visibility)) = q_sprite.get_mut(entity) {
+                if let Some(range) = widget.selection_range().filter(|_| widget.active) {
+                    sprite.custom_size =
+                        Some(Vec2::new(range.len() as f32 * CHAR_WIDTH, FONT_SIZE));
+                    transform.translation =
+                        (origin + Vec2::X * range.start as f32 * CHAR_WIDTH).extend(1.0);
+                    *visibility = Visibility::Inherited;
+                } else {
+                    *visibility = Visibility::Hidden;
                 }
             }
         }
@@ -211,7 +472,7 @@ fn input_widget_value<N, V: InputWidgetValue + 'static + Clone + Send + Sync>(
 
             if let Ok(mut node) = q_node.get_mut(parent.get()) {
                 (*node).set_value(widget.value.clone());
-                ev_conn.send(ConnectionEvent::Propagate);
+                ev_conn.send(ConnectionEvent::Propagate(parent.get()));
             }
         }
     }